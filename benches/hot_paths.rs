@@ -0,0 +1,143 @@
+use std::hint::black_box;
+use std::thread::available_parallelism;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use gdlauncher_test::{find_invalid_numbers_with_config, get_bounds, is_number_valid_in_sorted, is_number_valid_with, parse_numbers, Config, PairLookup, WindowBuffer, DEFAULT_DELIMITER, DEFAULT_ITEM_RANGE_SIZE, MIN_PARALLELISM};
+
+const WINDOW_SIZES: [usize; 4] = [25, 100, 500, 1000];
+const PIPELINE_BYTES: usize = 100 << 20;
+const PAIR_SEARCH_BYTES: usize = 4 << 20;
+const PARSE_BYTES: usize = 16 << 20;
+const PARSE_DIGITS: [u32; 4] = [3, 12, 24, 36];
+const MAX_GENERATED_NUMBER: u64 = 1000;
+const PAIR_LOOKUPS: [(&str, PairLookup); 2] = [("nested", PairLookup::Nested), ("hashset", PairLookup::Hashset)];
+
+// Deterministic LCG so runs are comparable without pulling a rand dependency.
+fn generate_numbers(count: usize) -> Vec<u128> {
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    (0..count)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((state >> 33) % MAX_GENERATED_NUMBER + 1) as u128
+        })
+        .collect()
+}
+
+// Newline-delimited buffer of roughly target_bytes, always ending with a newline.
+fn generate_buffer(target_bytes: usize) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(target_bytes + 8);
+    let mut numbers = generate_numbers(1 << 16).into_iter().cycle();
+    while buffer.len() < target_bytes {
+        buffer.extend_from_slice(numbers.next().unwrap().to_string().as_bytes());
+        buffer.push(b'\n');
+    }
+    buffer
+}
+
+// Buffer of roughly target_bytes holding numbers of exactly the given digits, leading zeros included.
+fn generate_digit_buffer(target_bytes: usize, digits: u32) -> Vec<u8> {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut buffer = Vec::with_capacity(target_bytes + digits as usize + 1);
+    while buffer.len() < target_bytes {
+        for _ in 0..digits {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            buffer.push(b'0' + ((state >> 33) % 10) as u8);
+        }
+        buffer.push(b'\n');
+    }
+    buffer
+}
+
+fn bench_is_number_valid(c: &mut Criterion) {
+    let mut group = c.benchmark_group("is_number_valid");
+    for window in WINDOW_SIZES {
+        let candidates = generate_numbers(window);
+        // Larger than any pair so every candidate pair is compared.
+        let target = 2 * MAX_GENERATED_NUMBER as u128 + 1;
+        // Both lookups at every size, to place HASHSET_MIN_ITEM_RANGE_SIZE.
+        for (name, pair_lookup) in PAIR_LOOKUPS {
+            let config = Config::builder().pair_lookup(pair_lookup).build();
+            group.bench_with_input(BenchmarkId::new(name, window), &candidates, |b, candidates| {
+                b.iter(|| is_number_valid_with(black_box(target), black_box(candidates), &config))
+            });
+        }
+        let mut sorted = candidates.clone();
+        sorted.sort_unstable();
+        group.bench_with_input(BenchmarkId::new("sorted", window), &sorted, |b, sorted| {
+            b.iter(|| is_number_valid_in_sorted(black_box(target), black_box(sorted)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_get_bounds(c: &mut Criterion) {
+    let data = generate_buffer(PIPELINE_BYTES);
+    c.bench_function("get_bounds", |b| {
+        b.iter(|| get_bounds(black_box(&data), MIN_PARALLELISM, DEFAULT_ITEM_RANGE_SIZE, DEFAULT_DELIMITER))
+    });
+}
+
+fn bench_pipeline(c: &mut Criterion) {
+    let data = generate_buffer(PIPELINE_BYTES);
+    let config = Config::builder().parallelism(MIN_PARALLELISM.max(available_parallelism().map_or(1, |cores| cores.get()))).build();
+
+    let mut group = c.benchmark_group("pipeline");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("find_invalid_numbers_100mb", |b| {
+        b.iter(|| find_invalid_numbers_with_config(black_box(&data), &config).unwrap())
+    });
+    group.finish();
+}
+
+// Whole runs, so the sorted window also pays for being kept sorted as it slides.
+fn bench_pair_search(c: &mut Criterion) {
+    let data = generate_buffer(PAIR_SEARCH_BYTES);
+    let mut group = c.benchmark_group("pair_search");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    for window in WINDOW_SIZES {
+        let configs = PAIR_LOOKUPS.map(|(name, pair_lookup)| (name, Config::builder().window(window).pair_lookup(pair_lookup).build()));
+        for (name, config) in configs.into_iter().chain([("sorted", Config::builder().window(window).sorted_window(true).build())]) {
+            group.bench_with_input(BenchmarkId::new(name, window), &config, |b, config| {
+                b.iter(|| find_invalid_numbers_with_config(black_box(&data), config).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+// Whole runs with each window buffer, to place DEQUE_MIN_ITEM_RANGE_SIZE.
+fn bench_window_buffer(c: &mut Criterion) {
+    let data = generate_buffer(PAIR_SEARCH_BYTES);
+    let mut group = c.benchmark_group("window_buffer");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    for window in WINDOW_SIZES {
+        for (name, window_buffer) in [("rotate", WindowBuffer::Rotate), ("deque", WindowBuffer::Deque)] {
+            let config = Config::builder().window(window).window_buffer(window_buffer).build();
+            group.bench_with_input(BenchmarkId::new(name, window), &config, |b, config| {
+                b.iter(|| find_invalid_numbers_with_config(black_box(&data), config).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+// Parsing alone, to compare the decimal loop with the simd feature across token lengths.
+fn bench_parse_numbers(c: &mut Criterion) {
+    let config = Config::default();
+    let mut group = c.benchmark_group("parse_numbers");
+    group.sample_size(10);
+    for digits in PARSE_DIGITS {
+        let data = generate_digit_buffer(PARSE_BYTES, digits);
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(digits), &data, |b, data| {
+            b.iter(|| parse_numbers(black_box(data), &config).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_is_number_valid, bench_pair_search, bench_window_buffer, bench_parse_numbers, bench_get_bounds, bench_pipeline);
+criterion_main!(benches);
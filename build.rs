@@ -0,0 +1,11 @@
+use std::env;
+
+// The parallel cfg stands for the parallel feature on every target but wasm32, which can't spawn the threads of rayon's
+// pool, so the chunks run on the calling thread there whatever the features.
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(parallel)");
+    let is_wasm = env::var("CARGO_CFG_TARGET_ARCH").is_ok_and(|arch| arch == "wasm32");
+    if env::var_os("CARGO_FEATURE_PARALLEL").is_some() && !is_wasm {
+        println!("cargo::rustc-cfg=parallel");
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::io::{self, BufWriter, Write};
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use gdlauncher_test::DEFAULT_ITEM_RANGE_SIZE;
+
+const ITEM_RANGE_SIZE_ENV: &str = "WINDOW_SIZE";
+const INVALID_FLAG: &str = "--invalid";
+const SEED_FLAG: &str = "--seed";
+const DEFAULT_INVALID_COUNT: usize = 10;
+const DEFAULT_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+const MAX_PREAMBLE_NUMBER: u64 = 1000;
+
+// Writes <count> newline-delimited numbers to stdout, where every number after the first WINDOW_SIZE is the sum of two
+// of the previous WINDOW_SIZE except for the injected invalid ones, whose lines are printed to stderr.
+// Valid numbers at least double every window, so only about a hundred windows fit in a u128.
+fn main() -> anyhow::Result<()> {
+    let args = parse_args()?;
+    let item_range_size = resolve_item_range_size()?;
+    if args.count < item_range_size + args.invalid {
+        bail!("{} numbers can't hold a preamble of {} and {} invalid numbers", args.count, item_range_size, args.invalid);
+    }
+
+    let mut rng = Lcg(args.seed);
+    let mut invalid_indexes = BTreeSet::new();
+    while invalid_indexes.len() < args.invalid {
+        invalid_indexes.insert(item_range_size + rng.below(args.count - item_range_size));
+    }
+
+    let mut numbers: Vec<u128> = (0..item_range_size).map(|_| rng.below(MAX_PREAMBLE_NUMBER as usize) as u128 + 1).collect();
+    numbers.reserve(args.count - item_range_size);
+    for idx in item_range_size..args.count {
+        let window = &numbers[idx - item_range_size..];
+        let number = if invalid_indexes.contains(&idx) {
+            eprintln!("Injected invalid number on line {}", idx + 1);
+            next_invalid(window)
+        } else {
+            next_valid(window, &mut rng)
+                .with_context(|| format!("Number on line {} overflows u128, generate fewer numbers or use a larger window", idx + 1))?
+        };
+        numbers.push(number);
+    }
+
+    let mut output = BufWriter::new(io::stdout().lock());
+    for number in &numbers {
+        writeln!(output, "{}", number)?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+
+struct Args {
+    count: usize,
+    invalid: usize,
+    seed: u64,
+}
+
+fn parse_args() -> anyhow::Result<Args> {
+    let mut count = None;
+    let mut args = Args { count: 0, invalid: DEFAULT_INVALID_COUNT, seed: DEFAULT_SEED };
+
+    let mut raw_args = env::args().skip(1);
+    while let Some(arg) = raw_args.next() {
+        match arg.as_str() {
+            INVALID_FLAG => args.invalid = parse_value(raw_args.next(), INVALID_FLAG)?,
+            SEED_FLAG => args.seed = parse_value(raw_args.next(), SEED_FLAG)?,
+            flag if flag.starts_with("--") => bail!("Unknown option {}", flag),
+            _ if count.is_some() => bail!("Unexpected argument {}", arg),
+            _ => count = Some(parse_value(Some(arg), "count")?),
+        }
+    }
+
+    args.count = count.context("Usage: gen <count> [--invalid N] [--seed N]")?;
+    Ok(args)
+}
+
+fn parse_value<T: FromStr>(value: Option<String>, name: &str) -> anyhow::Result<T> {
+    let value = value.with_context(|| format!("Missing value for {}", name))?;
+    value.parse().map_err(|_| anyhow::anyhow!("Invalid value '{}' for {}", value, name))
+}
+
+fn resolve_item_range_size() -> anyhow::Result<usize> {
+    let item_range_size = match env::var(ITEM_RANGE_SIZE_ENV) {
+        Ok(value) => usize::from_str(value.trim())
+            .with_context(|| format!("{} must be a positive integer, got '{}'", ITEM_RANGE_SIZE_ENV, value))?,
+        Err(env::VarError::NotPresent) => DEFAULT_ITEM_RANGE_SIZE,
+        Err(err) => return Err(err).context(ITEM_RANGE_SIZE_ENV),
+    };
+
+    // Valid numbers need two distinct addends.
+    if item_range_size < 2 {
+        bail!("{} must be at least 2", ITEM_RANGE_SIZE_ENV);
+    }
+
+    Ok(item_range_size)
+}
+
+
+// The smallest number plus a random other one, pairing with the smallest keeps the growth as slow as possible.
+fn next_valid(window: &[u128], rng: &mut Lcg) -> Option<u128> {
+    let smallest = smallest_index(window);
+    let other = (smallest + 1 + rng.below(window.len() - 1)) % window.len();
+    window[smallest].checked_add(window[other])
+}
+
+// Every pair sums to at least the two smallest numbers, so one less than their sum is never valid.
+// The preamble starts at 1, so that sum is at least 2.
+fn next_invalid(window: &[u128]) -> u128 {
+    let smallest = smallest_index(window);
+    let second = (0..window.len()).filter(|&idx| idx != smallest).min_by_key(|&idx| window[idx]).unwrap();
+    window[smallest] + window[second] - 1
+}
+
+fn smallest_index(window: &[u128]) -> usize {
+    (0..window.len()).min_by_key(|&idx| window[idx]).unwrap()
+}
+
+
+// Deterministic LCG, the same one the benchmarks use, so a seed always yields the same dataset.
+struct Lcg(u64);
+
+impl Lcg {
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as usize
+    }
+}
@@ -0,0 +1,3415 @@
+use std::borrow::Cow;
+use std::cmp::{max, Ordering as CmpOrdering};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::io::{self, BufRead, BufReader, Read};
+use std::mem;
+use std::ops::Range;
+#[cfg(parallel)]
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use memchr::memchr_iter;
+#[cfg(feature = "bigint")]
+pub use num_bigint::BigUint;
+#[cfg(parallel)]
+use rayon::iter::IndexedParallelIterator;
+#[cfg(parallel)]
+use rayon::iter::IntoParallelRefIterator;
+#[cfg(parallel)]
+use rayon::iter::ParallelIterator;
+#[cfg(parallel)]
+use rayon::slice::ParallelSlice;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, debug_span, warn};
+#[cfg(feature = "simd")]
+use wide::{i16x8, i32x8, u8x16};
+
+pub const MIN_PARALLELISM: usize = 4;
+pub const DEFAULT_ITEM_RANGE_SIZE: usize = 100;
+pub const DEFAULT_DELIMITER: u8 = b'\n';
+pub const DEFAULT_RADIX: u32 = 10;
+pub const DEFAULT_EPSILON: f64 = 1e-9;
+// Every separator is a single byte, Config::delimiter is a u8 and whitespace is matched byte by byte. With Config::crlf
+// the '\n' is the separator and the '\r' before it is left to the record, see Separator::record_end.
+const DELIMITER_LEN: usize = 1;
+// Chunk bounds point at the last byte of a record, the byte before its delimiter. Stepping over the delimiter from there
+// lands on the first byte of the next record, so the next chunk starts at right_bound + BOUND_TO_NEXT_RECORD, one byte
+// further with Config::crlf when a '\r' follows the bound, see Separator::next_record_start.
+// Data ending on a delimiter holds a number before it, so it is at least 2 bytes long and
+// data.len() - BOUND_TO_NEXT_RECORD is the last byte of its last record, see last_record_end for data without one.
+// That byte is the number itself for a single-character last line, or the previous delimiter after a blank last line,
+// which the chunk scan skips like any blank line. Between whitespace the next chunk may start on more whitespace.
+const BOUND_TO_NEXT_RECORD: usize = DELIMITER_LEN + 1;
+// Bounds of char::to_digit, which panics beyond them.
+const MIN_RADIX: u32 = 2;
+const MAX_RADIX: u32 = 36;
+// Digits a big number may have, longer tokens are rejected as NumberTooLong like numbers over u128::MAX otherwise.
+#[cfg(feature = "bigint")]
+const BIGINT_MAX_DIGITS: usize = 4096;
+// Decimal digits that always fit a u64.
+const U64_DECIMAL_DIGITS: usize = 19;
+// Decimal digits parsed at once with the simd feature, one per lane.
+#[cfg(feature = "simd")]
+const DECIMAL_CHUNK_LEN: usize = 16;
+// Characters a float may be written with, enough for any f64 as Display prints it, which never uses an exponent.
+const FLOAT_MAX_DIGITS: usize = 512;
+/// Smallest window the pair search is split across threads for, see Config::parallel_pair_search.
+pub const PARALLEL_MIN_ITEM_RANGE_SIZE: usize = 4096;
+/// Smallest window WindowBuffer::Auto keeps in a VecDeque.
+pub const DEQUE_MIN_ITEM_RANGE_SIZE: usize = 128;
+/// Smallest window PairLookup::Auto searches with a hashset.
+pub const HASHSET_MIN_ITEM_RANGE_SIZE: usize = 128;
+const PROGRESS_BATCH_BYTES: usize = 1 << 20;
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+const STREAM_BLOCK_SIZE: usize = 1 << 16;
+
+/// Failures of a run, all numbers must parse unless Config::skip_invalid_tokens is set.
+#[derive(Debug, Error)]
+pub enum ProcessError {
+    #[error("I/O error")]
+    Io(#[from] io::Error),
+    /// Bytes of the malformed token, without surrounding whitespace, and its 1-based line.
+    #[error("Invalid number {:?} on line {line}", String::from_utf8_lossy(bytes))]
+    ParseNumber { bytes: Vec<u8>, line: usize },
+    /// The input holds no number at all.
+    #[error("Input is empty")]
+    EmptyInput,
+    #[error("Window size must be greater than 0")]
+    ZeroWindow,
+    #[error("Radix {0} is not between 2 and 36")]
+    InvalidRadix(u32),
+    /// A token longer than any number of the radix can be, with its 1-based line.
+    #[error("Number on line {line} is longer than {max_len} characters")]
+    NumberTooLong { line: usize, max_len: usize },
+    /// The window and a read block of the forward scan take more than Config::max_memory on their own.
+    #[error("Window of {window} numbers needs {required} bytes, more than the memory limit of {limit} bytes")]
+    WindowOverMemoryLimit { window: usize, required: usize, limit: usize },
+    /// A record, with its 1-based line, longer than what Config::max_memory leaves next to the window.
+    #[error("Record on line {line} is longer than the {max_len} bytes left by the memory limit")]
+    RecordOverMemoryLimit { line: usize, max_len: usize },
+    /// The chunks analyze split the input into, numbered from 0, leave part of it out or overlap by the wrong number of
+    /// records, see Config::verify_bounds.
+    #[error("Bounds of chunk {chunk} are invalid, {reason}")]
+    InvalidBounds { chunk: usize, reason: &'static str },
+    /// One of Config::extra_windows is not smaller than the window the chunks are scanned with.
+    #[error("Extra window of {window} numbers must be smaller than the window of {item_range_size}")]
+    ExtraWindowTooLarge { window: usize, item_range_size: usize },
+}
+
+/// Settings of a run over newline-delimited numbers.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Number of chunks the input is split into.
+    pub parallelism: usize,
+    /// Count of preceding numbers each number must be a sum of two of.
+    pub item_range_size: usize,
+    /// Report and ignore malformed tokens instead of failing.
+    pub skip_invalid_tokens: bool,
+    /// Print the fraction of scanned bytes to stderr while running. Ignored on wasm32, which can't spawn the reporter thread.
+    pub progress: bool,
+    /// Single byte separating numbers.
+    pub delimiter: u8,
+    /// Any ASCII whitespace byte separates numbers instead of delimiter, so a line may hold several of them.
+    /// Lines are then counted on '\n' while skip_header still counts records.
+    pub whitespace_delimited: bool,
+    /// Records end on "\r\n" instead of delimiter, a lone '\n' still ends one. Chunk bounds then stop before the '\r', so
+    /// no chunk ends on it. Ignored with whitespace_delimited, which already separates on both bytes.
+    pub crlf: bool,
+    /// Record the addends of every validated number in the report.
+    pub explain: bool,
+    /// Base of the numbers, a matching 0b, 0o or 0x prefix is accepted for bases 2, 8 and 16.
+    pub radix: u32,
+    /// Ignore '_' separators between digits, a token made only of them is still malformed.
+    pub allow_underscores: bool,
+    /// Keep only the earliest invalid number. Streaming stops at it, so its counts only cover the numbers up to it.
+    pub first_only: bool,
+    /// Count of leading delimited records ignored before the window starts, line numbers still count them.
+    pub skip_header: usize,
+    /// Don't accept 0 as one of the addends, so a target is no longer valid just because it appears in its own window.
+    pub no_zero_pair: bool,
+    /// Only count the invalid numbers, leaving Report::invalid_numbers empty. Ignored with first_only.
+    pub count_only: bool,
+    /// Split the pair search of each number across rayon's pool once the window reaches PARALLEL_MIN_ITEM_RANGE_SIZE.
+    /// Pays off mostly for invalid numbers, which the sequential search has to check against the whole window.
+    /// Ignored without the parallel feature or on wasm32.
+    pub parallel_pair_search: bool,
+    /// Keep a sorted copy of the window, updated as it slides, and binary-search the complement of each candidate in it.
+    /// Replaces the nested loop and the hashset, so parallel_pair_search is ignored with it.
+    pub sorted_window: bool,
+    /// Bytes the forward scan of analyze_stream may hold at once. The window and one read block must fit, what remains
+    /// bounds a single record. None leaves it unbounded.
+    pub max_memory: Option<usize>,
+    /// Only validate the numbers whose record starts within these bytes of the data, header included in the offsets.
+    /// The numbers before the range still fill the window of its first numbers. Ignored by the forward scans of
+    /// analyze_stream, iter_invalid_numbers and find_invalid_numbers_with.
+    pub byte_range: Option<Range<usize>>,
+    /// Measure how long analyze spends on each phase into Report::phases. The clock is only read with it, as
+    /// wasm32-unknown-unknown has none.
+    pub time_phases: bool,
+    /// Take item_range_size from the first non-blank record after the skip_header ones, a decimal number which is
+    /// skipped like a header record. Overrides item_range_size.
+    pub window_from_header: bool,
+    /// Largest difference between a target and the sum of its addends for the floats of analyze_float.
+    pub epsilon: f64,
+    /// Keep only the earliest invalid numbers up to this many. Chunks stop scanning once the chunks before them found
+    /// enough and streaming stops at the last one kept, so the counts may not cover the whole input.
+    /// Ignored with first_only and count_only.
+    pub max_invalid: Option<usize>,
+    /// Skip the records whose first non-blank byte is this one like blank records, so they never reach the window.
+    /// With whitespace_delimited every token is a record, so a comment can't hold any whitespace.
+    pub comment_prefix: Option<u8>,
+    /// Count the pair checks of every search into Report::comparisons: each sum a nested loop tries, and each complement
+    /// a hashset or a binary search looks up.
+    pub count_comparisons: bool,
+    /// Keep the valid numbers with their location in Report::valid_numbers as well. Valid numbers usually far outnumber
+    /// the invalid ones, so the report then holds an entry for nearly every number of the input.
+    pub keep_valid: bool,
+    /// Record the validated numbers whose window holds some value more than once in Report::duplicate_windows, since
+    /// such a value may pair with itself. Ignored by the forward scans of analyze_stream, iter_invalid_numbers and
+    /// find_invalid_numbers_with.
+    pub report_dupes: bool,
+    /// Once this flag is set the chunks of analyze and the forward scan of analyze_stream stop and return what they found
+    /// so far, with Report::interrupted set.
+    pub interrupt: Option<Arc<AtomicBool>>,
+    /// Split analyze's input into chunks of this many delimited records, blank and comment ones included, instead of
+    /// parallelism chunks of about the same bytes. Finding the splits takes an extra pass over every delimiter, and each
+    /// chunk still parses the window of records after it again, so small values mostly add overlap.
+    pub records_per_chunk: Option<usize>,
+    /// How the chunks of analyze keep their window as it slides.
+    pub window_buffer: WindowBuffer,
+    /// How unsigned and signed numbers look for their addends in the unsorted window. Ignored with sorted_window, and
+    /// once parallel_pair_search splits the search.
+    pub pair_lookup: PairLookup,
+    /// Check that the chunks of analyze cover the whole input and overlap by exactly the window, failing with
+    /// ProcessError::InvalidBounds otherwise. Always checked, and asserted, in debug builds.
+    pub verify_bounds: bool,
+    /// Roughly how many invalid numbers analyze will find. Each chunk reserves its share of them, by the bytes it
+    /// owns, up front instead of growing its list as it finds them.
+    pub expected_invalid: Option<usize>,
+    /// Smaller windows every number is validated against as well, in the same scan as item_range_size, into
+    /// Report::window_results in the order given. Each must be between 1 and item_range_size - 1. Ignored with
+    /// first_only, count_only and max_invalid, and by the forward scans of analyze_stream, iter_invalid_numbers and
+    /// find_invalid_numbers_with.
+    pub extra_windows: Vec<usize>,
+    /// Measure the bytes, records and time of each chunk of analyze into Report::chunk_profiles. Every chunk reads the
+    /// clock on its own worker, and only with it, as wasm32-unknown-unknown has none.
+    pub profile_chunks: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            parallelism: MIN_PARALLELISM,
+            item_range_size: DEFAULT_ITEM_RANGE_SIZE,
+            skip_invalid_tokens: false,
+            progress: false,
+            delimiter: DEFAULT_DELIMITER,
+            whitespace_delimited: false,
+            crlf: false,
+            explain: false,
+            radix: DEFAULT_RADIX,
+            allow_underscores: false,
+            first_only: false,
+            skip_header: 0,
+            no_zero_pair: false,
+            count_only: false,
+            parallel_pair_search: false,
+            sorted_window: false,
+            max_memory: None,
+            byte_range: None,
+            time_phases: false,
+            window_from_header: false,
+            epsilon: DEFAULT_EPSILON,
+            max_invalid: None,
+            comment_prefix: None,
+            count_comparisons: false,
+            keep_valid: false,
+            report_dupes: false,
+            interrupt: None,
+            records_per_chunk: None,
+            window_buffer: WindowBuffer::Auto,
+            pair_lookup: PairLookup::Auto,
+            verify_bounds: false,
+            expected_invalid: None,
+            extra_windows: Vec::new(),
+            profile_chunks: false,
+        }
+    }
+}
+
+impl Config {
+    /// Builder starting from the default configuration.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder { config: Config::default() }
+    }
+}
+
+/// Builds a Config field by field, e.g. `Config::builder().window(100).parallelism(8).build()`.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    pub fn parallelism(mut self, parallelism: usize) -> Self {
+        self.config.parallelism = parallelism;
+        self
+    }
+
+    /// Sets Config::item_range_size.
+    pub fn window(mut self, item_range_size: usize) -> Self {
+        self.config.item_range_size = item_range_size;
+        self
+    }
+
+    pub fn skip_invalid_tokens(mut self, skip_invalid_tokens: bool) -> Self {
+        self.config.skip_invalid_tokens = skip_invalid_tokens;
+        self
+    }
+
+    pub fn progress(mut self, progress: bool) -> Self {
+        self.config.progress = progress;
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.config.delimiter = delimiter;
+        self
+    }
+
+    pub fn whitespace_delimited(mut self, whitespace_delimited: bool) -> Self {
+        self.config.whitespace_delimited = whitespace_delimited;
+        self
+    }
+
+    pub fn crlf(mut self, crlf: bool) -> Self {
+        self.config.crlf = crlf;
+        self
+    }
+
+    pub fn explain(mut self, explain: bool) -> Self {
+        self.config.explain = explain;
+        self
+    }
+
+    pub fn radix(mut self, radix: u32) -> Self {
+        self.config.radix = radix;
+        self
+    }
+
+    pub fn allow_underscores(mut self, allow_underscores: bool) -> Self {
+        self.config.allow_underscores = allow_underscores;
+        self
+    }
+
+    pub fn first_only(mut self, first_only: bool) -> Self {
+        self.config.first_only = first_only;
+        self
+    }
+
+    pub fn skip_header(mut self, skip_header: usize) -> Self {
+        self.config.skip_header = skip_header;
+        self
+    }
+
+    pub fn no_zero_pair(mut self, no_zero_pair: bool) -> Self {
+        self.config.no_zero_pair = no_zero_pair;
+        self
+    }
+
+    pub fn count_only(mut self, count_only: bool) -> Self {
+        self.config.count_only = count_only;
+        self
+    }
+
+    pub fn parallel_pair_search(mut self, parallel_pair_search: bool) -> Self {
+        self.config.parallel_pair_search = parallel_pair_search;
+        self
+    }
+
+    pub fn sorted_window(mut self, sorted_window: bool) -> Self {
+        self.config.sorted_window = sorted_window;
+        self
+    }
+
+    pub fn max_memory(mut self, max_memory: Option<usize>) -> Self {
+        self.config.max_memory = max_memory;
+        self
+    }
+
+    pub fn byte_range(mut self, byte_range: Option<Range<usize>>) -> Self {
+        self.config.byte_range = byte_range;
+        self
+    }
+
+    pub fn time_phases(mut self, time_phases: bool) -> Self {
+        self.config.time_phases = time_phases;
+        self
+    }
+
+    pub fn window_from_header(mut self, window_from_header: bool) -> Self {
+        self.config.window_from_header = window_from_header;
+        self
+    }
+
+    pub fn epsilon(mut self, epsilon: f64) -> Self {
+        self.config.epsilon = epsilon;
+        self
+    }
+
+    pub fn max_invalid(mut self, max_invalid: Option<usize>) -> Self {
+        self.config.max_invalid = max_invalid;
+        self
+    }
+
+    pub fn comment_prefix(mut self, comment_prefix: Option<u8>) -> Self {
+        self.config.comment_prefix = comment_prefix;
+        self
+    }
+
+    pub fn count_comparisons(mut self, count_comparisons: bool) -> Self {
+        self.config.count_comparisons = count_comparisons;
+        self
+    }
+
+    pub fn keep_valid(mut self, keep_valid: bool) -> Self {
+        self.config.keep_valid = keep_valid;
+        self
+    }
+
+    pub fn report_dupes(mut self, report_dupes: bool) -> Self {
+        self.config.report_dupes = report_dupes;
+        self
+    }
+
+    pub fn interrupt(mut self, interrupt: Option<Arc<AtomicBool>>) -> Self {
+        self.config.interrupt = interrupt;
+        self
+    }
+
+    pub fn records_per_chunk(mut self, records_per_chunk: Option<usize>) -> Self {
+        self.config.records_per_chunk = records_per_chunk;
+        self
+    }
+
+    pub fn window_buffer(mut self, window_buffer: WindowBuffer) -> Self {
+        self.config.window_buffer = window_buffer;
+        self
+    }
+
+    pub fn pair_lookup(mut self, pair_lookup: PairLookup) -> Self {
+        self.config.pair_lookup = pair_lookup;
+        self
+    }
+
+    pub fn verify_bounds(mut self, verify_bounds: bool) -> Self {
+        self.config.verify_bounds = verify_bounds;
+        self
+    }
+
+    pub fn expected_invalid(mut self, expected_invalid: Option<usize>) -> Self {
+        self.config.expected_invalid = expected_invalid;
+        self
+    }
+
+    pub fn extra_windows(mut self, extra_windows: Vec<usize>) -> Self {
+        self.config.extra_windows = extra_windows;
+        self
+    }
+
+    pub fn profile_chunks(mut self, profile_chunks: bool) -> Self {
+        self.config.profile_chunks = profile_chunks;
+        self
+    }
+
+    /// Values are checked when the config is used, e.g. a zero window fails with ProcessError::ZeroWindow.
+    pub fn build(self) -> Config {
+        self.config
+    }
+}
+
+/// Buffer the chunks of analyze keep their window in, sliding it by one number per step. The results are the same
+/// with each, only the speed differs: in the window_buffer benchmark both are even up to a window of 100, and the
+/// deque pulls ahead from there, running several times faster from a window of 2000.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WindowBuffer {
+    /// Rotate below DEQUE_MIN_ITEM_RANGE_SIZE, Deque from there on.
+    #[default]
+    Auto,
+    /// A flat array rotated by one slot per step, moving the whole window each time but always contiguous.
+    Rotate,
+    /// A VecDeque with room for two windows, moving the window only once every window's worth of steps to keep it
+    /// contiguous for the pair search.
+    Deque,
+}
+
+/// Search for the two addends of a number among the unsorted candidates of its window. The results are the same with
+/// each, only the speed differs, see the is_number_valid benchmark.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PairLookup {
+    /// Nested below HASHSET_MIN_ITEM_RANGE_SIZE, Hashset from there on.
+    #[default]
+    Auto,
+    /// Each candidate against the ones after it, quadratic in the window but without any allocation.
+    Nested,
+    /// Each candidate looks up its complement in a set of the candidates before it, linear in the window but filling
+    /// a new set for each number.
+    Hashset,
+}
+
+/// An invalid number with its location. Line is 1-based and byte_offset points at the first byte of the number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InvalidNumber<T = u128> {
+    pub value: T,
+    pub line: usize,
+    pub byte_offset: usize,
+}
+
+/// A valid number with its location, laid out as an InvalidNumber.
+pub type ValidNumber<T = u128> = InvalidNumber<T>;
+
+/// A validated number with the two preceding numbers summing to it, none when it is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Explanation<T = u128> {
+    pub value: T,
+    pub line: usize,
+    pub byte_offset: usize,
+    pub addends: Option<(T, T)>,
+}
+
+/// A validated number whose window holds some value more than once, see Config::report_dupes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DuplicateWindow<T = u128> {
+    pub value: T,
+    pub line: usize,
+    pub byte_offset: usize,
+    /// Distinct values occurring more than once in the window.
+    pub repeated: usize,
+}
+
+/// Invalid numbers under one of Config::extra_windows.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WindowResult<T = u128> {
+    pub window: usize,
+    /// Invalid numbers in file order.
+    pub invalid_numbers: Vec<InvalidNumber<T>>,
+    /// Numbers checked against a full window of this size, more than Report::validated as the window fills sooner.
+    pub validated: usize,
+}
+
+/// An f64 as parsed by analyze_float, always finite. Ordered by f64::total_cmp so it can be sorted and deduplicated.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Float(pub f64);
+
+impl PartialEq for Float {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for Float {}
+
+impl PartialOrd for Float {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Float {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+// Equal floats have the same bits under total_cmp.
+impl Hash for Float {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl fmt::Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Values of all invalid numbers in file order, using the default configuration with the given window.
+pub fn find_invalid_numbers(data: &[u8], item_range_size: usize) -> Result<Vec<u128>, ProcessError> {
+    let config = Config::builder().window(item_range_size).build();
+    let result = find_invalid_numbers_with_config(data, &config)?;
+    Ok(result.into_iter().map(|invalid| invalid.value).collect())
+}
+
+/// Same as find_invalid_numbers through analyze_reference: a single forward pass trying every pair of each window.
+/// The oracle the optimized paths are checked against, and the plainest statement of what they compute.
+pub fn find_invalid_numbers_reference(data: &[u8], item_range_size: usize) -> Result<Vec<u128>, ProcessError> {
+    let config = Config::builder().window(item_range_size).build();
+    let result = analyze_reference(data, &config)?.invalid_numbers;
+    Ok(result.into_iter().map(|invalid| invalid.value).collect())
+}
+
+/// All invalid numbers in file order.
+pub fn find_invalid_numbers_with_config(data: &[u8], config: &Config) -> Result<Vec<InvalidNumber>, ProcessError> {
+    Ok(analyze(data, config)?.invalid_numbers)
+}
+
+/// Every validated number in file order with a pair of preceding numbers summing to it, none when it is invalid.
+/// Runs analyze with Config::explain set, the rest of the config applies as given.
+pub fn explain_numbers(data: &[u8], config: &Config) -> Result<Vec<Explanation>, ProcessError> {
+    let config = Config { explain: true, ..config.clone() };
+    Ok(analyze(data, &config)?.explanations)
+}
+
+/// Invalid numbers of a run along with how many numbers were parsed and validated.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report<T = u128> {
+    /// Invalid numbers in file order, only the first one when Config::first_only is set.
+    pub invalid_numbers: Vec<InvalidNumber<T>>,
+    /// Count of invalid numbers found, also set when Config::count_only leaves invalid_numbers empty.
+    pub invalid_count: usize,
+    /// Numbers parsed from the input, each counted once even when chunks overlap.
+    pub parsed: usize,
+    /// Numbers checked against a full window, each counted once even when chunks overlap.
+    pub validated: usize,
+    /// Every validated number in file order, only filled when Config::explain is set.
+    pub explanations: Vec<Explanation<T>>,
+    /// Valid numbers in file order, only filled when Config::keep_valid is set.
+    pub valid_numbers: Vec<ValidNumber<T>>,
+    /// Validated numbers whose window holds duplicate values in file order, only filled when Config::report_dupes is set.
+    pub duplicate_windows: Vec<DuplicateWindow<T>>,
+    /// Only filled by analyze and its variants when Config::time_phases is set.
+    pub phases: Option<PhaseTimings>,
+    /// Pair checks of all searches when Config::count_comparisons is set, those of numbers validated by two overlapping
+    /// chunks included. Never filled by analyze_reference.
+    pub comparisons: Option<u64>,
+    /// Set when Config::interrupt stopped the run, the rest of the report then only covers part of the input.
+    pub interrupted: bool,
+    /// The results under each of Config::extra_windows, in the same order.
+    pub window_results: Vec<WindowResult<T>>,
+    /// One per chunk in input order, only filled by analyze and its variants when Config::profile_chunks is set.
+    pub chunk_profiles: Vec<ChunkProfile>,
+}
+
+/// Work of one chunk of analyze, see Config::profile_chunks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkProfile {
+    /// Bytes the chunk scanned as offsets into the input, its overlap with the next chunk included.
+    pub byte_range: Range<usize>,
+    /// Delimited records scanned, blank and comment ones included. Only up to where the chunk stopped when it stopped early.
+    pub records: usize,
+    /// From the start to the end of the chunk on its worker.
+    pub elapsed: Duration,
+}
+
+/// Time analyze spent on each phase, the merge of the chunk results is left out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PhaseTimings {
+    /// Splitting the input into chunks and numbering their first lines.
+    pub bounds: Duration,
+    /// Processing the chunks, on rayon's pool with the parallel feature.
+    pub compute: Duration,
+}
+
+impl<T> Report<T> {
+    /// Only exact when every invalid number was kept, see Config::first_only and Config::max_invalid.
+    pub fn valid(&self) -> usize {
+        self.validated - self.invalid_count
+    }
+
+    /// Earliest invalid number in the file, even once invalid_numbers was reordered.
+    pub fn first_invalid(&self) -> Option<&InvalidNumber<T>> {
+        self.invalid_numbers.iter().min_by_key(|invalid| invalid.byte_offset)
+    }
+}
+
+/// Runs the whole pipeline and reports the invalid numbers with parse and validation counts.
+/// The delimiter after the last number is optional.
+pub fn analyze(data: &[u8], config: &Config) -> Result<Report, ProcessError> {
+    analyze_numbers(data, config)
+}
+
+/// Same as analyze, with numbers parsed as i128 so they may be negative.
+pub fn analyze_signed(data: &[u8], config: &Config) -> Result<Report<i128>, ProcessError> {
+    analyze_numbers(data, config)
+}
+
+/// Same as analyze, with numbers of up to 4096 digits parsed as BigUint so they may exceed u128::MAX.
+/// Pairs are always searched through a hashset, Config::parallel_pair_search is ignored.
+#[cfg(feature = "bigint")]
+pub fn analyze_bigint(data: &[u8], config: &Config) -> Result<Report<BigUint>, ProcessError> {
+    analyze_numbers(data, config)
+}
+
+/// Same as analyze, with decimal numbers parsed as f64 so they may have a fraction or an exponent. A number is valid
+/// when two of its window sum to it within Config::epsilon, and Config::radix is ignored. Infinities and NaN are
+/// rejected as malformed tokens. Pairs are searched without the hashset, whose exact lookups don't allow a tolerance.
+pub fn analyze_float(data: &[u8], config: &Config) -> Result<Report<Float>, ProcessError> {
+    analyze_numbers(data, config)
+}
+
+fn analyze_numbers<T: Number>(data: &[u8], config: &Config) -> Result<Report<T>, ProcessError> {
+    // Read before the header is cut off and the rest is split into chunks, which all need the window.
+    let config = with_header_window(data, config)?;
+    let config: &Config = &config;
+    let Config { parallelism, item_range_size, progress, skip_header, .. } = *config;
+    let separator = Separator::new(config);
+    let parallelism = max(1, parallelism);
+    if item_range_size == 0 {
+        return Err(ProcessError::ZeroWindow);
+    }
+    check_extra_windows(config)?;
+    let format = NumberFormat::new::<T>(config)?;
+
+    // The header is cut off before chunking so every chunk sees the same records as a single forward scan.
+    let header_len = get_header_len(data, skip_header, separator);
+    let header_lines = count_lines(&data[..header_len], separator);
+    let data = &data[header_len..];
+
+    // Nothing to parse, and the bounds math below assumes at least one number.
+    if data.iter().all(u8::is_ascii_whitespace) {
+        return Err(ProcessError::EmptyInput);
+    }
+
+    // A byte range is cut out like the header, along with the numbers just before it that fill the window of its first
+    // ones. Those are parsed again by the chunks, so they are taken off the parsed count.
+    let (range_start, range_end, preamble) = match &config.byte_range {
+        Some(range) => get_range_bounds::<T>(data, range, header_len, item_range_size, separator, format),
+        None => (0, data.len(), 0),
+    };
+    let lines_before = header_lines + count_lines(&data[..range_start], separator);
+    let offset_before = header_len + range_start;
+    let data = &data[range_start..range_end];
+    if data.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Report { window_results: empty_window_results(config), ..Report::default() });
+    }
+
+    let bounds_start = config.time_phases.then(Instant::now);
+    let split_points = match config.records_per_chunk {
+        Some(records_per_chunk) => record_split_points(data, records_per_chunk, separator),
+        None => byte_split_points(data, parallelism),
+    };
+    let fills_window = |record: &[u8]| fills_window::<T>(record, format);
+    let bounds = debug_span!("get_bounds", chunks = split_points.len()).in_scope(|| split_bounds(data, &split_points, item_range_size, separator, fills_window));
+    debug!(?bounds, "Split the input into {} chunks", bounds.len());
+    if config.verify_bounds || cfg!(debug_assertions) {
+        let verified = verify_bounds(data, &bounds, item_range_size, separator, fills_window);
+        debug_assert!(verified.is_ok(), "{verified:?}");
+        verified?;
+    }
+    let first_lines: Vec<usize> = get_first_lines(data, &bounds, separator).into_iter().map(|line| line + lines_before).collect();
+    let compute_start = config.time_phases.then(Instant::now);
+    // Numbers from the next chunk's left bound on are parsed again by that chunk.
+    let owned_until: Vec<usize> = bounds.iter().skip(1).map(|(left, _)| *left).chain([usize::MAX]).collect();
+    let progress = progress.then(|| Progress::new(bounds.iter().map(|(left, right)| right + 1 - left).sum()));
+    // Without any invalid number to keep, every chunk would stop at once and its parsed numbers would go uncounted.
+    let invalid_counts = max_invalid(config).filter(|&max_invalid| max_invalid > 0).map(|max_invalid| InvalidCounts::new(bounds.len(), max_invalid));
+    let results: Vec<Result<ChunkResult<T>, (usize, ProcessError)>> = with_progress_reporter(progress.as_ref(), || {
+        #[cfg(parallel)]
+        let chunk_args = bounds.par_iter().zip(first_lines.par_iter()).zip(owned_until.par_iter());
+        #[cfg(not(parallel))]
+        let chunk_args = bounds.iter().zip(first_lines.iter()).zip(owned_until.iter());
+        chunk_args
+            .enumerate()
+            .map(|(idx, ((bounds, first_line), owned_until))| {
+                // Read on the worker itself, so the chunks never wait on each other to be timed.
+                let start = config.profile_chunks.then(Instant::now);
+                let mut chunk = process(data, *bounds, *first_line, *owned_until, config, progress.as_ref(), invalid_counts.as_ref().map(|counts| (counts, idx)))?;
+                chunk.profile = start.map(|start| ChunkProfile { byte_range: bounds.0..bounds.1 + 1, records: chunk.records, elapsed: start.elapsed() });
+                Ok(chunk)
+            })
+            .collect()
+    });
+    // Each chunk reports its first malformed token, the earliest of those is the first of the input however it was split.
+    let mut chunks = Vec::with_capacity(results.len());
+    let mut first_error: Option<(usize, ProcessError)> = None;
+    for result in results {
+        match result {
+            Ok(chunk) => chunks.push(chunk),
+            Err((byte_offset, err)) if first_error.as_ref().is_none_or(|(first_offset, _)| byte_offset < *first_offset) => first_error = Some((byte_offset, err)),
+            Err(_) => {}
+        }
+    }
+    if let Some((_, err)) = first_error {
+        return Err(err);
+    }
+    let phases = bounds_start.zip(compute_start).map(|(bounds_start, compute_start)| PhaseTimings { bounds: compute_start - bounds_start, compute: compute_start.elapsed() });
+
+    // Validated ranges of different chunks may overlap, the numbers in the overlap are counted only once.
+    chunks.sort_unstable_by_key(|chunk| chunk.validated_range);
+    let mut validated = 0;
+    let mut covered_until = None;
+    for chunk in &chunks {
+        let Some((from, to)) = chunk.validated_range else {
+            continue;
+        };
+        validated += chunk.validated;
+        if let Some(covered_until) = covered_until.filter(|&covered_until| from <= covered_until) {
+            validated -= count_token_starts(data, from, to.min(covered_until), separator, config.comment_prefix);
+        }
+        covered_until = max(covered_until, Some(to));
+    }
+
+    let parsed = chunks.iter().map(|chunk| chunk.parsed).sum::<usize>() - preamble;
+    let counted: usize = chunks.iter().map(|chunk| chunk.invalid_count).sum();
+    let mut explanations: Vec<Explanation<T>> = chunks.iter_mut().filter_map(|chunk| chunk.explanations.take()).flatten().collect();
+    let mut valid_numbers: Vec<ValidNumber<T>> = chunks.iter_mut().filter_map(|chunk| chunk.valid_numbers.take()).flatten().collect();
+    let mut duplicate_windows: Vec<DuplicateWindow<T>> = chunks.iter_mut().filter_map(|chunk| chunk.duplicate_windows.take()).flatten().collect();
+    let mut invalid_numbers: Vec<InvalidNumber<T>> = chunks.iter_mut().flat_map(|chunk| mem::take(&mut chunk.invalid_numbers)).collect();
+
+    into_file_order(&mut invalid_numbers, |invalid| invalid.byte_offset);
+    // Each chunk kept its earliest invalid number, the earliest of those is the global first.
+    if config.first_only {
+        invalid_numbers.truncate(1);
+    }
+    into_file_order(&mut explanations, |explanation| explanation.byte_offset);
+    into_file_order(&mut valid_numbers, |valid| valid.byte_offset);
+    into_file_order(&mut duplicate_windows, |duplicate| duplicate.byte_offset);
+    // Chunks that stopped early leave gaps past the last invalid number kept, so the other lists end there.
+    if let Some(max_invalid) = max_invalid(config).filter(|&max_invalid| invalid_numbers.len() >= max_invalid) {
+        invalid_numbers.truncate(max_invalid);
+        let last_offset = invalid_numbers.last().map_or(0, |invalid| invalid.byte_offset);
+        explanations.retain(|explanation| max_invalid > 0 && explanation.byte_offset <= last_offset);
+        valid_numbers.retain(|valid| max_invalid > 0 && valid.byte_offset <= last_offset);
+        duplicate_windows.retain(|duplicate| max_invalid > 0 && duplicate.byte_offset <= last_offset);
+    }
+    let mut invalid_count = invalid_numbers.len();
+    // Only the invalid numbers past a chunk's owned range were kept, the owner already counted those it validated itself.
+    if counts_only(config) {
+        let validated_by_owner = |offset: usize| {
+            chunks.iter()
+                .find(|chunk| (chunk.owned.0..chunk.owned.1).contains(&offset))
+                .and_then(|chunk| chunk.validated_range)
+                .is_some_and(|(from, to)| (from..=to).contains(&offset))
+        };
+        invalid_count = counted + invalid_numbers.iter().filter(|invalid| !validated_by_owner(invalid.byte_offset)).count();
+        invalid_numbers.clear();
+    }
+    // Lines already account for the header and the bytes before the range through first_lines, offsets are still
+    // relative to the range.
+    invalid_numbers.iter_mut().for_each(|invalid| invalid.byte_offset += offset_before);
+    explanations.iter_mut().for_each(|explanation| explanation.byte_offset += offset_before);
+    valid_numbers.iter_mut().for_each(|valid| valid.byte_offset += offset_before);
+    duplicate_windows.iter_mut().for_each(|duplicate| duplicate.byte_offset += offset_before);
+    let comparisons = config.count_comparisons.then(|| chunks.iter().map(|chunk| chunk.comparisons).sum());
+    let interrupted = chunks.iter().any(|chunk| chunk.interrupted);
+    let mut chunk_profiles: Vec<ChunkProfile> = chunks.iter_mut().filter_map(|chunk| chunk.profile.take()).collect();
+    chunk_profiles.sort_unstable_by_key(|profile| profile.byte_range.start);
+    for profile in &mut chunk_profiles {
+        profile.byte_range = profile.byte_range.start + offset_before..profile.byte_range.end + offset_before;
+    }
+
+    // Every number the chunks validated was checked against each extra window too, only the first ones of the data are
+    // left, which the smaller windows already fill.
+    let mut window_results = Vec::with_capacity(extra_windows(config).len());
+    for (idx, &window) in extra_windows(config).iter().enumerate() {
+        let mut invalid_numbers: Vec<InvalidNumber<T>> = chunks.iter_mut().flat_map(|chunk| mem::take(&mut chunk.window_results[idx].1)).collect();
+        let (head_invalid, head_validated) = match interrupted {
+            false => validate_head::<T>(data, config, window, preamble, lines_before)?,
+            true => (Vec::new(), 0),
+        };
+        invalid_numbers.extend(head_invalid);
+        into_file_order(&mut invalid_numbers, |invalid| invalid.byte_offset);
+        invalid_numbers.iter_mut().for_each(|invalid| invalid.byte_offset += offset_before);
+        window_results.push(WindowResult { window, invalid_numbers, validated: validated + head_validated });
+    }
+    Ok(Report { invalid_numbers, invalid_count, parsed, validated, explanations, valid_numbers, duplicate_windows, phases, comparisons, interrupted, window_results, chunk_profiles })
+}
+
+// Chunks are scanned backwards and merged in whatever order rayon yields, so restore file order.
+// Overlapping chunk windows can evaluate the same number twice, which is collapsed by position rather than value
+// so legitimately repeated values are kept.
+fn into_file_order<N>(numbers: &mut Vec<N>, byte_offset: impl Fn(&N) -> usize) {
+    numbers.sort_unstable_by_key(&byte_offset);
+    numbers.dedup_by_key(|number| byte_offset(number));
+}
+
+// Numbers validated against window but not against item_range_size: the first item_range_size numbers of the data from
+// the window-th on, short of the preamble ones before a byte range. Lines are numbered as in the whole input, offsets
+// are still relative to data. Returns the invalid ones and how many were validated.
+fn validate_head<T: Number>(data: &[u8], config: &Config, window: usize, preamble: usize, lines_before: usize) -> Result<(Vec<InvalidNumber<T>>, usize), ProcessError> {
+    let head_config = Config { item_range_size: window, skip_header: 0, window_from_header: false, max_memory: None, ..config.clone() };
+    let mut scan = ForwardScan::<_, T>::new(data, &head_config)?;
+    let (mut invalid_numbers, mut validated) = (Vec::new(), 0);
+    // The scan validates the window-th number first.
+    for idx in window..config.item_range_size {
+        let Some(Explanation { value, line, byte_offset, addends }) = scan.next_validated()? else {
+            break;
+        };
+        if idx < preamble {
+            continue;
+        }
+        validated += 1;
+        if addends.is_none() {
+            invalid_numbers.push(InvalidNumber { value, line: line + lines_before, byte_offset });
+        }
+    }
+    Ok((invalid_numbers, validated))
+}
+
+// The windows of Config::extra_windows that apply, none when not every invalid number is listed.
+fn extra_windows(config: &Config) -> &[usize] {
+    if config.first_only || config.count_only || config.max_invalid.is_some() {
+        return &[];
+    }
+    &config.extra_windows
+}
+
+fn check_extra_windows(config: &Config) -> Result<(), ProcessError> {
+    for &window in extra_windows(config) {
+        if window == 0 {
+            return Err(ProcessError::ZeroWindow);
+        }
+        if window >= config.item_range_size {
+            return Err(ProcessError::ExtraWindowTooLarge { window, item_range_size: config.item_range_size });
+        }
+    }
+    Ok(())
+}
+
+// Results of the extra windows for an input without any number to validate.
+fn empty_window_results<T>(config: &Config) -> Vec<WindowResult<T>> {
+    extra_windows(config).iter().map(|&window| WindowResult { window, invalid_numbers: Vec::new(), validated: 0 }).collect()
+}
+
+/// Same as analyze, reading the input forward in blocks so it never has to be in memory as a whole.
+/// Runs on a single thread, so parallelism and progress are ignored.
+pub fn analyze_stream(reader: impl Read, config: &Config) -> Result<Report, ProcessError> {
+    analyze_stream_numbers(reader, config)
+}
+
+/// Same as analyze_stream, with numbers parsed as i128 so they may be negative.
+pub fn analyze_stream_signed(reader: impl Read, config: &Config) -> Result<Report<i128>, ProcessError> {
+    analyze_stream_numbers(reader, config)
+}
+
+/// Same as analyze_stream, with numbers parsed as in analyze_bigint.
+/// Config::max_memory only accounts for the inline part of each number, not its digits.
+#[cfg(feature = "bigint")]
+pub fn analyze_stream_bigint(reader: impl Read, config: &Config) -> Result<Report<BigUint>, ProcessError> {
+    analyze_stream_numbers(reader, config)
+}
+
+/// Same as analyze_stream, with numbers parsed as in analyze_float.
+pub fn analyze_stream_float(reader: impl Read, config: &Config) -> Result<Report<Float>, ProcessError> {
+    analyze_stream_numbers(reader, config)
+}
+
+fn analyze_stream_numbers<T: Number>(reader: impl Read, config: &Config) -> Result<Report<T>, ProcessError> {
+    let mut scan = ForwardScan::new(BufReader::with_capacity(STREAM_BLOCK_SIZE, reader), config)?;
+    let mut report = Report::default();
+    while max_invalid(config).is_none_or(|max_invalid| report.invalid_count < max_invalid) {
+        if is_interrupted(config) {
+            report.interrupted = true;
+            break;
+        }
+        let Some(explanation) = scan.next_validated()? else {
+            break;
+        };
+        report.validated += 1;
+        let is_invalid = explanation.addends.is_none();
+        if is_invalid {
+            report.invalid_count += 1;
+        }
+        if is_invalid && !counts_only(config) {
+            let Explanation { value, line, byte_offset, .. } = explanation.clone();
+            report.invalid_numbers.push(InvalidNumber { value, line, byte_offset });
+        }
+        if !is_invalid && config.keep_valid {
+            let Explanation { value, line, byte_offset, .. } = explanation.clone();
+            report.valid_numbers.push(ValidNumber { value, line, byte_offset });
+        }
+        if config.explain {
+            report.explanations.push(explanation);
+        }
+        if config.first_only && is_invalid {
+            break;
+        }
+    }
+    report.parsed = scan.parsed;
+    report.comparisons = config.count_comparisons.then_some(scan.comparisons);
+    Ok(report)
+}
+
+/// Brute-force counterpart of analyze for auditing it: parses the whole input on the calling thread and tries every
+/// pair of every window. Slow by design and never fills Report::explanations.
+pub fn analyze_reference(data: &[u8], config: &Config) -> Result<Report, ProcessError> {
+    analyze_reference_numbers(data, config)
+}
+
+/// Same as analyze_reference, with numbers parsed as i128 so they may be negative.
+pub fn analyze_reference_signed(data: &[u8], config: &Config) -> Result<Report<i128>, ProcessError> {
+    analyze_reference_numbers(data, config)
+}
+
+/// Same as analyze_reference, with numbers parsed as in analyze_bigint.
+#[cfg(feature = "bigint")]
+pub fn analyze_reference_bigint(data: &[u8], config: &Config) -> Result<Report<BigUint>, ProcessError> {
+    analyze_reference_numbers(data, config)
+}
+
+/// Same as analyze_reference, with numbers parsed as in analyze_float.
+pub fn analyze_reference_float(data: &[u8], config: &Config) -> Result<Report<Float>, ProcessError> {
+    analyze_reference_numbers(data, config)
+}
+
+// Shares only the token parsing with the pipeline, so chunking and the pair searches are checked independently.
+fn analyze_reference_numbers<T: Number>(data: &[u8], config: &Config) -> Result<Report<T>, ProcessError> {
+    let config = with_header_window(data, config)?;
+    let config: &Config = &config;
+    let Config { item_range_size, skip_invalid_tokens, skip_header, no_zero_pair, first_only, .. } = *config;
+    if item_range_size == 0 {
+        return Err(ProcessError::ZeroWindow);
+    }
+    check_extra_windows(config)?;
+    let separator = Separator::new(config);
+    let format = NumberFormat::new::<T>(config)?;
+    let body = &data[get_header_len(data, skip_header, separator)..];
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Err(ProcessError::EmptyInput);
+    }
+
+    let mut numbers = Vec::new();
+    let (mut offset, mut line) = (0, 1);
+    for (idx, token) in data.split(|&byte| separator.matches(byte)).enumerate() {
+        let (token_offset, token_line) = (offset, line);
+        offset += token.len() + 1;
+        if data.get(offset - 1).is_some_and(|&byte| separator.ends_line(byte)) {
+            line += 1;
+        }
+        if idx < skip_header || is_skipped_record(token, config.comment_prefix) {
+            continue;
+        }
+        if let Some(value) = parse_token(token, skip_invalid_tokens, format, || token_line)? {
+            numbers.push(InvalidNumber { value, line: token_line, byte_offset: token_offset });
+        }
+    }
+
+    let search = PairSearch::new(config);
+    let is_addend = |number: &T| !no_zero_pair || *number != T::default();
+    let in_range = |number: &InvalidNumber<T>| config.byte_range.as_ref().is_none_or(|range| range.contains(&number.byte_offset));
+    let is_valid = |number: &InvalidNumber<T>, window: &[InvalidNumber<T>]| window.iter().enumerate().any(|(first_idx, first)| {
+        window[first_idx + 1..].iter().any(|second| {
+            is_addend(&first.value) && is_addend(&second.value) && T::is_sum(&number.value, &first.value, &second.value, search)
+        })
+    });
+    let mut report = Report { parsed: numbers.iter().filter(|number| in_range(number)).count(), ..Report::default() };
+    for &window_size in extra_windows(config) {
+        let mut window_result = WindowResult { window: window_size, ..WindowResult::default() };
+        for (idx, number) in numbers.iter().enumerate().skip(window_size).filter(|(_, number)| in_range(number)) {
+            window_result.validated += 1;
+            if !is_valid(number, &numbers[idx - window_size..idx]) {
+                window_result.invalid_numbers.push(number.clone());
+            }
+        }
+        report.window_results.push(window_result);
+    }
+    for (idx, number) in numbers.iter().enumerate().skip(item_range_size) {
+        if !in_range(number) {
+            continue;
+        }
+        let window = &numbers[idx - item_range_size..idx];
+        let is_valid = is_valid(number, window);
+        report.validated += 1;
+        if !is_valid {
+            report.invalid_numbers.push(number.clone());
+        } else if config.keep_valid {
+            report.valid_numbers.push(number.clone());
+        }
+        if config.report_dupes {
+            let mut values: Vec<&T> = window.iter().map(|number| &number.value).collect();
+            values.sort_unstable();
+            let repeated = values.chunk_by(|first, second| first == second).filter(|values| values.len() > 1).count();
+            if repeated > 0 {
+                let InvalidNumber { value, line, byte_offset } = number.clone();
+                report.duplicate_windows.push(DuplicateWindow { value, line, byte_offset, repeated });
+            }
+        }
+    }
+
+    if first_only {
+        report.invalid_numbers.truncate(1);
+    }
+    if let Some(max_invalid) = max_invalid(config) {
+        report.invalid_numbers.truncate(max_invalid);
+        let last_offset = report.invalid_numbers.last().map_or(0, |invalid| invalid.byte_offset);
+        report.duplicate_windows.retain(|duplicate| max_invalid > 0 && duplicate.byte_offset <= last_offset);
+    }
+    report.invalid_count = report.invalid_numbers.len();
+    if counts_only(config) {
+        report.invalid_numbers.clear();
+    }
+    Ok(report)
+}
+
+/// Lazily yields the invalid numbers in file order, scanning forward on the calling thread.
+/// Unlike analyze, which collects the results of every chunk before returning, only the window is kept in memory.
+/// A malformed token is yielded as an error and ends the iteration unless Config::skip_invalid_tokens is set.
+pub fn iter_invalid_numbers<'a>(data: &'a [u8], config: &Config) -> Result<impl Iterator<Item = Result<InvalidNumber, ProcessError>> + 'a, ProcessError> {
+    Ok(InvalidNumbers { scan: ForwardScan::new(data, config)?, done: false })
+}
+
+/// Calls f with each invalid number in file order as soon as it is found, without collecting them.
+/// The scan runs forward on the calling thread as in analyze_stream, so f is never called concurrently and needs neither
+/// Send nor Sync, while parallelism and progress are ignored. Stops after the first call when Config::first_only is set,
+/// or after Config::max_invalid calls.
+pub fn find_invalid_numbers_with<F: FnMut(InvalidNumber)>(data: &[u8], config: &Config, mut f: F) -> Result<(), ProcessError> {
+    let mut scan = ForwardScan::new(data, config)?;
+    let mut found = 0;
+    while max_invalid(config).is_none_or(|max_invalid| found < max_invalid) {
+        let Some(Explanation { value, line, byte_offset, addends }) = scan.next_validated()? else {
+            break;
+        };
+        if addends.is_some() {
+            continue;
+        }
+        f(InvalidNumber { value, line, byte_offset });
+        found += 1;
+        if config.first_only {
+            break;
+        }
+    }
+    Ok(())
+}
+
+struct InvalidNumbers<'a> {
+    scan: ForwardScan<&'a [u8], u128>,
+    done: bool,
+}
+
+impl Iterator for InvalidNumbers<'_> {
+    type Item = Result<InvalidNumber, ProcessError>;
+
+    // Empty data simply has no invalid numbers.
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.scan.next_validated() {
+                Ok(Some(Explanation { value, line, byte_offset, addends: None })) => return Some(Ok(InvalidNumber { value, line, byte_offset })),
+                Ok(Some(_)) => {}
+                Ok(None) | Err(ProcessError::EmptyInput) => self.done = true,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
+
+// Forward scan keeping only the window, shared by the stream and iterator APIs.
+// The window keeps the newest number in front, so candidates are checked in the same order as in the reverse chunk scan.
+struct ForwardScan<R, T> {
+    reader: R,
+    item_range_size: usize,
+    skip_invalid_tokens: bool,
+    separator: Separator,
+    skip_header: usize,
+    comment_prefix: Option<u8>,
+    pair_search: PairSearch,
+    sorted: Option<SortedWindow<T>>,
+    format: NumberFormat,
+    window: VecDeque<T>,
+    token: Vec<u8>,
+    max_memory: Option<usize>,
+    max_record_len: u64,
+    // Set until the window is read from the data, see Config::window_from_header.
+    window_pending: bool,
+    byte_offset: usize,
+    records: usize,
+    // Lines ended so far.
+    line: usize,
+    has_content: bool,
+    parsed: usize,
+    comparisons: u64,
+}
+
+impl<R: BufRead, T: Number> ForwardScan<R, T> {
+    fn new(reader: R, config: &Config) -> Result<Self, ProcessError> {
+        let Config { item_range_size, skip_invalid_tokens, skip_header, window_from_header, .. } = *config;
+        if item_range_size == 0 && !window_from_header {
+            return Err(ProcessError::ZeroWindow);
+        }
+        let format = NumberFormat::new::<T>(config)?;
+        // The window read from the data is only known once its record was read, which the memory limit then accounts for.
+        let max_record_len = match config.max_memory {
+            Some(limit) if !window_from_header => max_record_len::<T>(item_range_size, config.sorted_window, limit)?,
+            _ => u64::MAX,
+        };
+
+        Ok(ForwardScan {
+            reader,
+            item_range_size,
+            skip_invalid_tokens,
+            separator: Separator::new(config),
+            skip_header,
+            comment_prefix: config.comment_prefix,
+            pair_search: PairSearch::new(config),
+            sorted: config.sorted_window.then(SortedWindow::default),
+            format,
+            window: VecDeque::with_capacity(item_range_size),
+            token: Vec::new(),
+            max_memory: config.max_memory,
+            max_record_len,
+            window_pending: window_from_header,
+            byte_offset: 0,
+            records: 0,
+            line: 0,
+            has_content: false,
+            parsed: 0,
+            comparisons: 0,
+        })
+    }
+
+    // The next number checked against a full window, with its addends. None once the input is exhausted.
+    fn next_validated(&mut self) -> Result<Option<Explanation<T>>, ProcessError> {
+        loop {
+            self.token.clear();
+            let read = self.separator.read_record(&mut (&mut self.reader).take(self.max_record_len), &mut self.token)?;
+            if read == 0 {
+                break;
+            }
+            // A record filling the limit without its delimiter is only complete when it ends the input.
+            let is_delimited = self.token.last().is_some_and(|&byte| self.separator.matches(byte));
+            if read as u64 == self.max_record_len && !is_delimited && !self.reader.fill_buf()?.is_empty() {
+                return Err(ProcessError::RecordOverMemoryLimit { line: self.line + 1, max_len: read });
+            }
+            let token_offset = self.byte_offset;
+            self.byte_offset += read;
+            self.records += 1;
+            let line = self.line + 1;
+            if self.token.last().is_some_and(|&byte| self.separator.ends_line(byte)) {
+                self.line += 1;
+            }
+            if self.records <= self.skip_header {
+                continue;
+            }
+
+            // Only the last token can miss its delimiter, it is then taken up to the end of the input.
+            let bytes = match self.token.split_last() {
+                Some((&last, bytes)) if self.separator.matches(last) => bytes,
+                _ => &self.token[..],
+            };
+            if bytes.trim_ascii().is_empty() {
+                continue;
+            }
+            // Past the window record a comment makes the input non-empty, as it does for analyze.
+            if is_skipped_record(bytes, self.comment_prefix) {
+                self.has_content |= !self.window_pending;
+                continue;
+            }
+            if self.window_pending {
+                self.item_range_size = parse_window_record(bytes, || line)?;
+                if self.item_range_size == 0 {
+                    return Err(ProcessError::ZeroWindow);
+                }
+                if let Some(limit) = self.max_memory {
+                    self.max_record_len = max_record_len::<T>(self.item_range_size, self.sorted.is_some(), limit)?;
+                }
+                self.window_pending = false;
+                continue;
+            }
+            self.has_content = true;
+
+            let Some(number) = parse_token(bytes, self.skip_invalid_tokens, self.format, || line)? else {
+                continue;
+            };
+            self.parsed += 1;
+            if self.window.len() < self.item_range_size {
+                self.window.push_front(number);
+                continue;
+            }
+
+            let candidates = self.window.make_contiguous();
+            let addends = match &mut self.sorted {
+                Some(sorted) => sorted.find_addends(&number, candidates, self.pair_search, &mut self.comparisons),
+                None => T::find_addends(&number, candidates, self.pair_search, &mut self.comparisons),
+            };
+            if let Some(oldest) = self.window.pop_back() {
+                if let Some(sorted) = &mut self.sorted {
+                    sorted.slide(&oldest, number.clone());
+                }
+            }
+            self.window.push_front(number.clone());
+            return Ok(Some(Explanation { value: number, line, byte_offset: token_offset, addends }));
+        }
+
+        if !self.has_content {
+            return Err(ProcessError::EmptyInput);
+        }
+        Ok(None)
+    }
+}
+
+// Bytes left for a record once the window, with its sorted copy, and a STREAM_BLOCK_SIZE read block fit the limit.
+fn max_record_len<T>(item_range_size: usize, sorted_window: bool, limit: usize) -> Result<u64, ProcessError> {
+    let copies = if sorted_window { 2 } else { 1 };
+    let required = item_range_size.saturating_mul(mem::size_of::<T>() * copies).saturating_add(STREAM_BLOCK_SIZE);
+    if required >= limit {
+        return Err(ProcessError::WindowOverMemoryLimit { window: item_range_size, required, limit });
+    }
+    Ok((limit - required) as u64)
+}
+
+fn count_lines(data: &[u8], separator: Separator) -> usize {
+    data.iter().filter(|&&byte| separator.ends_line(byte)).count()
+}
+
+// Count of tokens starting within [from, to], leaving out the skipped ones.
+fn count_token_starts(data: &[u8], from: usize, to: usize, separator: Separator, comment_prefix: Option<u8>) -> usize {
+    let token_end = |idx: usize| separator.positions(&data[idx..]).next().map_or(data.len(), |len| idx + len);
+    (from..=to)
+        .filter(|&idx| (idx == 0 || separator.matches(data[idx - 1])) && !is_skipped_record(&data[idx..token_end(idx)], comment_prefix))
+        .count()
+}
+
+// Records holding no number, blank ones and with Config::comment_prefix those starting with it.
+fn is_skipped_record(record: &[u8], comment_prefix: Option<u8>) -> bool {
+    match record.trim_ascii_start().first() {
+        None => true,
+        Some(&byte) => Some(byte) == comment_prefix,
+    }
+}
+
+
+// Bytes scanned across all chunks, overlaps included.
+struct Progress {
+    consumed: AtomicUsize,
+    total: usize,
+}
+
+impl Progress {
+    fn new(total: usize) -> Self {
+        Progress { consumed: AtomicUsize::new(0), total }
+    }
+
+    // Workers publish in PROGRESS_BATCH_BYTES batches so the shared counter is rarely contended.
+    fn add(&self, bytes: usize) {
+        self.consumed.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn percentage(&self) -> f64 {
+        self.consumed.load(Ordering::Relaxed) as f64 * 100.0 / max(1, self.total) as f64
+    }
+}
+
+// Runs work while a reporter thread prints the progress to stderr every PROGRESS_INTERVAL.
+fn with_progress_reporter<T>(progress: Option<&Progress>, work: impl FnOnce() -> T) -> T {
+    let Some(progress) = progress.filter(|_| !cfg!(target_arch = "wasm32")) else {
+        return work();
+    };
+
+    let (stop_sender, stop_receiver) = mpsc::channel::<()>();
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(PROGRESS_INTERVAL) {
+                eprint!("\rProgress: {:.1}%", progress.percentage());
+            }
+            eprintln!("\rProgress: {:.1}%", progress.percentage());
+        });
+
+        let result = work();
+        drop(stop_sender);
+        result
+    })
+}
+
+
+/// Chunk (left, right) byte bounds of newline-delimited data, as analyze splits it with the given parallelism and window.
+/// Each left bound is the first byte of a record and each right bound the last byte of one, inclusive. The first chunk
+/// starts at 0 and the last one ends with the last record. Every other chunk overlaps the next one by the next one's
+/// first window non-blank records, or up to the end of the data when fewer of them remain, which only fill the window
+/// of the next chunk's first validated number. At most parallelism chunks are returned, none for empty data.
+pub fn compute_bounds(data: &[u8], parallelism: usize, window: usize) -> Vec<(usize, usize)> {
+    get_bounds(data, parallelism, window, DEFAULT_DELIMITER)
+}
+
+/// Same as compute_bounds for data separated by another delimiter.
+pub fn get_bounds(data: &[u8], parallelism: usize, item_range_size: usize, delimiter: u8) -> Vec<(usize, usize)> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+    let split_points = byte_split_points(data, max(1, parallelism));
+    split_bounds(data, &split_points, item_range_size, Separator::Byte(delimiter), |record| !is_skipped_record(record, None))
+}
+
+// Every chunk ends on the record holding its split point, or reaching past it.
+fn byte_split_points(data: &[u8], parallelism: usize) -> Vec<usize> {
+    let bytes_per_chunk = data.len() / parallelism;
+    (1..=parallelism).map(|num_core| num_core * bytes_per_chunk).collect()
+}
+
+// Every records_per_chunk-th delimiter, which ends the last record of a chunk, with the end of the data for the records
+// left after the last one.
+fn record_split_points(data: &[u8], records_per_chunk: usize, separator: Separator) -> Vec<usize> {
+    let records_per_chunk = max(1, records_per_chunk);
+    let mut split_points: Vec<usize> = separator.positions(data).skip(records_per_chunk - 1).step_by(records_per_chunk).collect();
+    split_points.push(data.len());
+    split_points
+}
+
+// fills_window tells the records holding a number for the window, only those count towards the overlap.
+fn split_bounds(data: &[u8], split_points: &[usize], item_range_size: usize, separator: Separator, fills_window: impl Fn(&[u8]) -> bool) -> Vec<(usize, usize)> {
+    let mut bounds: Vec<(usize, usize)> = Vec::with_capacity(split_points.len());
+
+    let mut previous_left_bound = 0;
+    for (num_core, &split_point) in split_points.iter().enumerate() {
+        let left_bound = previous_left_bound;
+        // Records longer than a chunk may already reach past the next split point, whose delimiter then ended the
+        // previous segment, so the search starts no earlier than this chunk.
+        let ini_pos = max(split_point, left_bound);
+        let (right_bound, mut right_bound_overflow) = get_right_bounds(data, ini_pos, item_range_size, separator, &fills_window);
+        previous_left_bound = separator.next_record_start(data, right_bound);
+        // Byte split points are rounded down and lines may be longer than a chunk, so the chunk reaching the last line
+        // is stretched to the end of the data and no chunk is left starting past it.
+        let is_last = num_core + 1 == split_points.len() || previous_left_bound >= data.len();
+        if is_last {
+            right_bound_overflow = last_record_end(data, separator);
+        }
+        bounds.push((left_bound, right_bound_overflow));
+        if is_last {
+            break;
+        }
+    }
+
+    bounds
+}
+
+// Whether the record holds a number the chunk scans add to their window. Blank records, comments and malformed tokens,
+// even skipped ones, don't.
+fn fills_window<T: Number>(record: &[u8], format: NumberFormat) -> bool {
+    let token = record.trim_ascii();
+    !token.is_empty() && token.len() <= format.max_len && parse_number_from_str_buffer::<T>(token, format).is_some()
+}
+
+// Checks the bounds against what the chunk scans rely on: together they run from the first byte to the end of the
+// last record, each chunk starts past the start of the previous one and no later than right after its end, and the
+// overlap holds exactly the item_range_size numbers filling the window of the next chunk, fewer only when it reaches
+// the end of the data. A short overlap leaves the first numbers of the next chunk unvalidated, a gap loses records.
+fn verify_bounds(data: &[u8], bounds: &[(usize, usize)], item_range_size: usize, separator: Separator, fills_window: impl Fn(&[u8]) -> bool) -> Result<(), ProcessError> {
+    let invalid = |chunk: usize, reason: &'static str| Err(ProcessError::InvalidBounds { chunk, reason });
+    let data_end = last_record_end(data, separator);
+    match (bounds.first(), bounds.last()) {
+        (Some(&(first_left, _)), Some(&(_, last_right))) => {
+            if first_left != 0 {
+                return invalid(0, "the first chunk doesn't start at the beginning of the data");
+            }
+            if last_right != data_end {
+                return invalid(bounds.len() - 1, "the last chunk doesn't end with the last record");
+            }
+        }
+        _ => return invalid(0, "no chunk covers the data"),
+    }
+    for (chunk, pair) in bounds.windows(2).enumerate() {
+        let ((left, right), (next_left, _)) = (pair[0], pair[1]);
+        if next_left <= left {
+            return invalid(chunk + 1, "the chunk doesn't start past the start of the previous one");
+        }
+        if next_left > separator.next_record_start(data, right) - DELIMITER_LEN {
+            return invalid(chunk + 1, "a gap is left before the chunk");
+        }
+        let overlap = &data[next_left..=right];
+        let numbers = overlap.split(|&byte| separator.matches(byte)).filter(|record| fills_window(record)).count();
+        if numbers > item_range_size {
+            return invalid(chunk, "the overlap with the next chunk holds more numbers than the window");
+        }
+        if numbers < item_range_size && right != data_end {
+            return invalid(chunk, "the overlap with the next chunk doesn't fill its window");
+        }
+    }
+    Ok(())
+}
+
+// Bounds of the records starting within the byte range, once the header_len bytes of the header were cut off data.
+// Like the chunk bounds the start only moves back, over up to item_range_size numbers which only fill the window of
+// the first numbers in the range, and their count is returned as well: (start, end, preamble).
+// The end is past the delimiter of the last record, or the end of the data for a last record without one.
+fn get_range_bounds<T: Number>(data: &[u8], range: &Range<usize>, header_len: usize, item_range_size: usize, separator: Separator, format: NumberFormat) -> (usize, usize, usize) {
+    let next_record_start = |pos: usize| {
+        let pos = pos.saturating_sub(header_len).min(data.len());
+        if pos == 0 || separator.matches(data[pos - 1]) {
+            return pos;
+        }
+        separator.positions(&data[pos..]).next().map_or(data.len(), |idx| pos + idx + 1)
+    };
+    let range_start = next_record_start(range.start);
+    let range_end = max(range_start, next_record_start(range.end));
+
+    // Every start is just past a delimiter, so the previous record ends on the byte before it, except for a range past an
+    // undelimited last record. Malformed tokens don't fill the window, they are still parsed again by the chunks to be
+    // skipped or rejected there.
+    let (mut start, mut preamble) = (range_start, 0);
+    while start > 0 && preamble < item_range_size {
+        let record_end = if separator.matches(data[start - 1]) { start - 1 } else { start };
+        let record_start = data[..record_end].iter().rposition(|&byte| separator.matches(byte)).map_or(0, |idx| idx + 1);
+        let token = data[record_start..record_end].trim_ascii();
+        if !token.is_empty() && token.len() <= format.max_len && parse_number_from_str_buffer::<T>(token, format).is_some() {
+            preamble += 1;
+        }
+        start = record_start;
+    }
+    (start, range_end, preamble)
+}
+
+// With Config::window_from_header, the config with the window read from the data and its record, along with any blank
+// or comment ones before it, added to the header records. The record is read as in parse_window_record.
+fn with_header_window<'a>(data: &[u8], config: &'a Config) -> Result<Cow<'a, Config>, ProcessError> {
+    if !config.window_from_header {
+        return Ok(Cow::Borrowed(config));
+    }
+    let separator = Separator::new(config);
+    let mut start = get_header_len(data, config.skip_header, separator);
+    let mut skip_header = config.skip_header;
+    while start < data.len() {
+        let end = separator.positions(&data[start..]).next().map_or(data.len(), |idx| start + idx);
+        skip_header += 1;
+        if !is_skipped_record(&data[start..end], config.comment_prefix) {
+            let line = || 1 + count_lines(&data[..start], separator);
+            let item_range_size = parse_window_record(&data[start..end], line)?;
+            return Ok(Cow::Owned(Config { item_range_size, skip_header, window_from_header: false, ..config.clone() }));
+        }
+        start = end + 1;
+    }
+    Err(ProcessError::EmptyInput)
+}
+
+// The window is a decimal count whatever the radix of the numbers, a malformed one fails like a malformed number.
+fn parse_window_record(record: &[u8], line: impl FnOnce() -> usize) -> Result<usize, ProcessError> {
+    let record = record.trim_ascii();
+    let window = std::str::from_utf8(record).ok().and_then(|record| record.parse().ok());
+    window.ok_or_else(|| ProcessError::ParseNumber { bytes: record.to_vec(), line: line() })
+}
+
+// Length of the first skip_header delimited records, the whole data if it has fewer of them.
+fn get_header_len(data: &[u8], skip_header: usize, separator: Separator) -> usize {
+    if skip_header == 0 {
+        return 0;
+    }
+    separator.positions(data).nth(skip_header - 1).map_or(data.len(), |idx| idx + 1)
+}
+
+// 1-based line of the first number of each chunk, from the newlines between consecutive left bounds.
+fn get_first_lines(data: &[u8], bounds: &[(usize, usize)], separator: Separator) -> Vec<usize> {
+    #[cfg(parallel)]
+    let windows = bounds.par_windows(2);
+    #[cfg(not(parallel))]
+    let windows = bounds.windows(2);
+    let newlines: Vec<usize> = windows
+        .map(|window| {
+            let (from, to) = (window[0].0, window[1].0.min(data.len()));
+            count_lines(&data[from..to], separator)
+        })
+        .collect();
+
+    let mut first_lines = Vec::with_capacity(bounds.len());
+    let mut line = 1;
+    first_lines.push(line);
+    for count in newlines {
+        line += count;
+        first_lines.push(line);
+    }
+
+    first_lines
+}
+
+// Last byte of the last record, the byte before the final delimiter or the final byte when the delimiter is missing.
+// The chunk scan runs backwards from its right bound, so an undelimited last number is parsed like any other.
+// With Config::crlf a final "\r\n" is left out as a whole, the data holds a number before it.
+fn last_record_end(data: &[u8], separator: Separator) -> usize {
+    let last = data.len() - 1;
+    if separator.matches(data[last]) {
+        separator.record_end(data, last) - 1
+    } else {
+        last
+    }
+}
+
+// Calculate the next valid index within the bounds, considering an overflow of item_range_size items.
+// This ensures the first item_range_size items of each segment are processed. The segment itself ends on the first
+// delimiter, so the overflow ends item_range_size delimiters after it: the next chunk validates from its
+// (item_range_size + 1)th number on, and every number before it must be validated here.
+// Returns (right_bound, right_bound_overflow), both pointing at the last byte of a record:
+// - with a single delimiter after ini_pos both are the byte before it,
+// - with up to item_range_size + 1 delimiters the overflow defaults to the end of the last record,
+// - with more, the overflow stops before the (item_range_size + 1)th delimiter.
+// With ini_pos on the final delimiter or in an undelimited last record both default to the end of the last record,
+// the caller never passes an ini_pos past the data.
+// Between whitespace the delimiters are the whitespace bytes, so numbers rather than lines are counted.
+fn get_right_bounds(data: &[u8], ini_pos: usize, item_range_size: usize, separator: Separator, fills_window: impl Fn(&[u8]) -> bool) -> (usize, usize) {
+    let mut right_bound = last_record_end(data, separator);
+    let mut right_bound_overflow = right_bound;
+
+    let mut overflow_count = 0;
+    let mut record_start = ini_pos;
+    for idx in separator.positions(&data[ini_pos..]).map(|idx| ini_pos + idx) {
+        let record = &data[record_start..idx];
+        record_start = idx + 1;
+        let record_end = separator.record_end(data, idx);
+        // Blank lines, comments and malformed tokens hold no number for the window, so they don't count towards the
+        // overflow. A delimiter at position zero, or a "\r\n" there, ends a blank first line and no segment can end
+        // before the data starts, so it is skipped as well.
+        if record_end == 0 || (overflow_count > 0 && !fills_window(record)) {
+            continue;
+        }
+        overflow_count += 1;
+        if overflow_count == 1 {
+            right_bound = record_end - 1;
+        }
+        if overflow_count == item_range_size + 1 {
+            right_bound_overflow = record_end - 1;
+            break;
+        }
+    }
+
+    (right_bound, right_bound_overflow)
+}
+
+
+// Byte offset of the token and its line index counted backwards from the chunk end.
+type Position = (usize, usize);
+
+// Keeping just the earliest invalid number needs them all in a chunk, so counting only doesn't apply then.
+fn counts_only(config: &Config) -> bool {
+    config.count_only && !config.first_only
+}
+
+fn max_invalid(config: &Config) -> Option<usize> {
+    config.max_invalid.filter(|_| !config.first_only && !config.count_only)
+}
+
+// The share of Config::expected_invalid of a chunk owning owned_len bytes. Every number takes a digit and a
+// delimiter at least, which also bounds the reservation for an oversized hint.
+fn expected_invalid_capacity(config: &Config, data_len: usize, owned_len: usize) -> usize {
+    let Some(expected_invalid) = config.expected_invalid else {
+        return 0;
+    };
+    if counts_only(config) || config.first_only || data_len == 0 {
+        return 0;
+    }
+    let share = (expected_invalid as u128 * owned_len as u128).div_ceil(data_len as u128);
+    let capacity = share.min(owned_len.div_ceil(2) as u128) as usize;
+    max_invalid(config).map_or(capacity, |max_invalid| capacity.min(max_invalid))
+}
+
+fn is_interrupted(config: &Config) -> bool {
+    config.interrupt.as_ref().is_some_and(|interrupt| interrupt.load(Ordering::Relaxed))
+}
+
+// Invalid numbers each chunk found in its owned range, published as its reverse scan goes. Those of the chunks before
+// a chunk all precede its own, so once they add up to max_invalid none of its numbers can be among the earliest ones.
+// A single shared counter would not do, the chunks scanning backwards find their latest numbers first.
+struct InvalidCounts {
+    counts: Vec<AtomicUsize>,
+    max_invalid: usize,
+}
+
+impl InvalidCounts {
+    fn new(chunks: usize, max_invalid: usize) -> Self {
+        InvalidCounts { counts: (0..chunks).map(|_| AtomicUsize::new(0)).collect(), max_invalid }
+    }
+
+    fn publish(&self, chunk: usize, count: usize) {
+        self.counts[chunk].store(count, Ordering::Relaxed);
+    }
+
+    fn enough_before(&self, chunk: usize) -> bool {
+        self.counts[..chunk].iter().map(|count| count.load(Ordering::Relaxed)).sum::<usize>() >= self.max_invalid
+    }
+}
+
+// Outcome of a single chunk. The validated range holds the byte offsets of the first and last validated numbers.
+// When counting only, invalid numbers in the owned range are just counted and only those past it are kept so analyze
+// can tell whether the next chunk counted them already.
+struct ChunkResult<T> {
+    invalid_numbers: Vec<InvalidNumber<T>>,
+    invalid_count: usize,
+    // Invalid numbers in the owned range, whether kept or just counted.
+    owned_invalid: usize,
+    owned: (usize, usize),
+    count_only: bool,
+    first_only: bool,
+    pair_search: PairSearch,
+    sorted: Option<SortedWindow<T>>,
+    parsed: usize,
+    validated: usize,
+    validated_range: Option<(usize, usize)>,
+    explanations: Option<Vec<Explanation<T>>>,
+    valid_numbers: Option<Vec<ValidNumber<T>>>,
+    window_duplicates: Option<WindowDuplicates<T>>,
+    duplicate_windows: Option<Vec<DuplicateWindow<T>>>,
+    // Kept when the chunk is discarded, the checks were still made.
+    comparisons: u64,
+    // Set when Config::interrupt stopped the scan before the left bound.
+    interrupted: bool,
+    // Invalid numbers under each of the extra windows.
+    window_results: Vec<(usize, Vec<InvalidNumber<T>>)>,
+    // Delimited records scanned so far, the first one of the chunk has no delimiter in it.
+    records: usize,
+    // Filled in by analyze_numbers around process, see Config::profile_chunks.
+    profile: Option<ChunkProfile>,
+}
+
+impl<T: Number> ChunkResult<T> {
+    fn validate(&mut self, numbers: &[T], (byte_offset, reverse_line): Position) {
+        // Stores the reverse line index in line until process translates it.
+        let addends = match &mut self.sorted {
+            Some(sorted) => sorted.find_addends(&numbers[0], &numbers[1..], self.pair_search, &mut self.comparisons),
+            None => T::find_addends(&numbers[0], &numbers[1..], self.pair_search, &mut self.comparisons),
+        };
+        if addends.is_none() {
+            // The scan runs backwards, so each invalid number precedes the ones found before it.
+            if self.first_only {
+                self.invalid_numbers.clear();
+            }
+            let owned = (self.owned.0..self.owned.1).contains(&byte_offset);
+            if owned {
+                self.owned_invalid += 1;
+            }
+            if self.count_only && owned {
+                self.invalid_count += 1;
+            } else {
+                self.invalid_numbers.push(InvalidNumber { value: numbers[0].clone(), line: reverse_line, byte_offset });
+            }
+        }
+        if let Some(valid_numbers) = self.valid_numbers.as_mut().filter(|_| addends.is_some()) {
+            valid_numbers.push(ValidNumber { value: numbers[0].clone(), line: reverse_line, byte_offset });
+        }
+        // The nearest candidates come first, and a number invalid against the whole window is invalid against any part of it.
+        for (window, invalid_numbers) in &mut self.window_results {
+            let is_valid = addends.is_some() && T::find_addends(&numbers[0], &numbers[1..=*window], self.pair_search, &mut self.comparisons).is_some();
+            if !is_valid {
+                invalid_numbers.push(InvalidNumber { value: numbers[0].clone(), line: reverse_line, byte_offset });
+            }
+        }
+        if let Some(explanations) = &mut self.explanations {
+            explanations.push(Explanation { value: numbers[0].clone(), line: reverse_line, byte_offset, addends });
+        }
+        if let (Some(window_duplicates), Some(duplicate_windows)) = (&mut self.window_duplicates, &mut self.duplicate_windows) {
+            let repeated = window_duplicates.repeated(&numbers[1..]);
+            if repeated > 0 {
+                duplicate_windows.push(DuplicateWindow { value: numbers[0].clone(), line: reverse_line, byte_offset, repeated });
+            }
+        }
+
+        // Numbers are validated from the chunk end backwards.
+        self.validated += 1;
+        self.validated_range = match self.validated_range {
+            None => Some((byte_offset, byte_offset)),
+            Some((_, to)) => Some((byte_offset, to)),
+        };
+    }
+
+    // Drops everything found so far, leaving a result that parsed and validated nothing.
+    fn discard(mut self) -> Self {
+        self.invalid_numbers.clear();
+        self.invalid_count = 0;
+        self.parsed = 0;
+        self.validated = 0;
+        self.validated_range = None;
+        if let Some(explanations) = &mut self.explanations {
+            explanations.clear();
+        }
+        if let Some(valid_numbers) = &mut self.valid_numbers {
+            valid_numbers.clear();
+        }
+        if let Some(duplicate_windows) = &mut self.duplicate_windows {
+            duplicate_windows.clear();
+        }
+        self.window_results.iter_mut().for_each(|(_, invalid_numbers)| invalid_numbers.clear());
+        self
+    }
+}
+
+// With invalid counts, the chunk at the given index gives up as soon as the chunks before it found enough invalid
+// numbers, returning a result that validated nothing. Errors come with the byte offset of the token they were met on.
+fn process<T: Number>(data: &[u8], (left_bound, right_bound): (usize, usize), first_line: usize, owned_until: usize, config: &Config, progress: Option<&Progress>, invalid_counts: Option<(&InvalidCounts, usize)>) -> Result<ChunkResult<T>, (usize, ProcessError)> {
+    let _span = debug_span!("process", left_bound, right_bound).entered();
+    let Config { item_range_size, skip_invalid_tokens, explain, first_only, comment_prefix, .. } = *config;
+    let separator = Separator::new(config);
+    let interrupt = config.interrupt.as_deref();
+
+    let format = NumberFormat::new::<T>(config).map_err(|err| (left_bound, err))?;
+    let str_buffer_len = format.max_len;
+    let mut str_buffer = vec![0; str_buffer_len];
+    let mut str_buffer_idx = str_buffer_len;
+    // Set once a token no longer fits the buffer, its remaining bytes are dropped up to its delimiter.
+    let mut too_long = false;
+    // First non-blank byte of the token so far, still known once it no longer fits the buffer, to tell comments apart.
+    let mut token_start = None;
+    // The scan runs backwards, so each malformed token precedes the ones met before it. The scan still goes on to the
+    // left bound to report the first one of the chunk, but leaves the numbers past it unvalidated.
+    let mut first_error = None;
+
+    let mut window = ScanWindow::new(item_range_size, config.window_buffer);
+    let mut reverse_line = 0;
+    // Relative index down to which the scanned bytes were already added to progress.
+    let mut reported_idx = right_bound + 1 - left_bound;
+
+    let mut chunk = ChunkResult {
+        invalid_numbers: Vec::with_capacity(expected_invalid_capacity(config, data.len(), owned_until.min(right_bound + 1) - left_bound)),
+        invalid_count: 0,
+        owned_invalid: 0,
+        owned: (left_bound, owned_until),
+        count_only: counts_only(config),
+        first_only,
+        pair_search: PairSearch::new(config),
+        sorted: config.sorted_window.then(SortedWindow::default),
+        parsed: 0,
+        validated: 0,
+        validated_range: None,
+        explanations: explain.then(Vec::new),
+        valid_numbers: config.keep_valid.then(Vec::new),
+        window_duplicates: config.report_dupes.then(WindowDuplicates::default),
+        duplicate_windows: config.report_dupes.then(Vec::new),
+        comparisons: 0,
+        interrupted: false,
+        window_results: extra_windows(config).iter().map(|&window| (window, Vec::new())).collect(),
+        records: 1,
+        profile: None,
+    };
+    let publish = |chunk: &ChunkResult<T>| invalid_counts.is_some_and(|(counts, idx)| {
+        counts.publish(idx, chunk.owned_invalid);
+        counts.enough_before(idx)
+    });
+    if publish(&chunk) {
+        if let Some(progress) = progress {
+            progress.add(reported_idx);
+        }
+        debug!("Chunk skipped, enough invalid numbers before it");
+        return Ok(chunk);
+    }
+    for (idx, &byte) in data[left_bound..=right_bound].iter().enumerate().rev() {
+        if !separator.matches(byte) {
+            if !byte.is_ascii_whitespace() {
+                token_start = Some(byte);
+            }
+            // Trailing whitespace is never stored, and leading whitespace that no longer fits is dropped,
+            // so padded tokens don't overflow the buffer.
+            if byte.is_ascii_whitespace() && (str_buffer_idx == str_buffer_len || str_buffer_idx == 0) {
+                continue;
+            }
+            if str_buffer_idx == 0 {
+                too_long = true;
+                continue;
+            }
+            str_buffer_idx -= 1;
+            str_buffer[str_buffer_idx] = byte;
+            continue;
+        }
+
+        if reported_idx - idx >= PROGRESS_BATCH_BYTES {
+            if let Some(progress) = progress {
+                progress.add(reported_idx - idx);
+            }
+            reported_idx = idx;
+            if first_error.is_none() && publish(&chunk) {
+                if let Some(progress) = progress {
+                    progress.add(reported_idx);
+                }
+                debug!("Chunk stopped, enough invalid numbers before it");
+                return Ok(chunk.discard());
+            }
+        }
+        // Checked on every record, a single search over a large window can take a while. The numbers still in the
+        // buffer are left unvalidated, the lines before idx are counted to translate those of the numbers found.
+        if first_error.is_none() && interrupt.is_some_and(|interrupt| interrupt.load(Ordering::Relaxed)) {
+            debug!(parsed = chunk.parsed, validated = chunk.validated, "Chunk interrupted");
+            chunk.interrupted = true;
+            let last_line = first_line + count_lines(&data[left_bound..=left_bound + idx], separator) + reverse_line;
+            return Ok(translate_lines(chunk, last_line));
+        }
+
+        chunk.records += 1;
+        let position = (left_bound + idx + 1, reverse_line);
+        if separator.ends_line(byte) {
+            reverse_line += 1;
+        }
+        // Blank lines carry no number and leave the numbers buffer untouched.
+        if str_buffer_idx == str_buffer_len {
+            continue;
+        }
+        let line = || first_line + count_lines(&data[left_bound..position.0], separator);
+        // Comments are dropped like blank lines, whatever their length.
+        let token = if comment_prefix.is_some() && token_start == comment_prefix {
+            Ok(None)
+        } else if too_long {
+            reject_token(ProcessError::NumberTooLong { line: line(), max_len: str_buffer_len }, skip_invalid_tokens)
+        } else {
+            parse_token(&str_buffer[str_buffer_idx..str_buffer_len], skip_invalid_tokens, format, line)
+        };
+        match token {
+            Ok(Some(new_number)) if first_error.is_none() => {
+                if position.0 < owned_until {
+                    chunk.parsed += 1;
+                }
+                add_number(&mut chunk, &mut window, new_number, position);
+            }
+            Ok(_) => {}
+            Err(err) => first_error = Some((position.0, err)),
+        }
+
+        // Reset
+        str_buffer[str_buffer_idx..str_buffer_len].fill(0);
+        str_buffer_idx = str_buffer_len;
+        too_long = false;
+        token_start = None;
+    }
+
+    if let Some(progress) = progress {
+        progress.add(reported_idx);
+    }
+
+    // A skipped first token leaves numbers[0] as the last number still pending validation.
+    if str_buffer_idx < str_buffer_len {
+        let token = if comment_prefix.is_some() && token_start == comment_prefix {
+            None
+        } else if too_long {
+            reject_token(ProcessError::NumberTooLong { line: first_line, max_len: str_buffer_len }, skip_invalid_tokens).map_err(|err| (left_bound, err))?
+        } else {
+            parse_token(&str_buffer[str_buffer_idx..str_buffer_len], skip_invalid_tokens, format, || first_line).map_err(|err| (left_bound, err))?
+        };
+        if let Some(error) = first_error {
+            return Err(error);
+        }
+        if let Some(new_number) = token {
+            if left_bound < owned_until {
+                chunk.parsed += 1;
+            }
+            add_number(&mut chunk, &mut window, new_number, (left_bound, reverse_line));
+        }
+    }
+
+    // With item_range_size numbers or fewer every number belongs to the preamble.
+    if !window.is_full() {
+        return Ok(chunk);
+    }
+
+    let (numbers, target_position) = window.numbers();
+    chunk.validate(numbers, target_position);
+    publish(&chunk);
+
+    let chunk = translate_lines(chunk, first_line + reverse_line);
+    debug!(parsed = chunk.parsed, validated = chunk.validated, invalid = chunk.invalid_numbers.len() + chunk.invalid_count, "Chunk processed");
+    Ok(chunk)
+}
+
+// The total line count is only known once the reverse scan is done, so lines are translated afterwards from the line of
+// the chunk end.
+fn translate_lines<T>(mut chunk: ChunkResult<T>, last_line: usize) -> ChunkResult<T> {
+    chunk.invalid_numbers.iter_mut().for_each(|invalid| invalid.line = last_line - invalid.line);
+    chunk.explanations.iter_mut().flatten().for_each(|explanation| explanation.line = last_line - explanation.line);
+    chunk.valid_numbers.iter_mut().flatten().for_each(|valid| valid.line = last_line - valid.line);
+    chunk.duplicate_windows.iter_mut().flatten().for_each(|duplicate| duplicate.line = last_line - duplicate.line);
+    chunk.window_results.iter_mut().flat_map(|(_, invalid_numbers)| invalid_numbers).for_each(|invalid| invalid.line = last_line - invalid.line);
+    chunk
+}
+
+
+/// Sliding window with a running sum over at least 2 numbers. Returns the (min, max) of the first matching range.
+// All numbers are unsigned, so the window only has to shrink from the left while the sum exceeds the target.
+pub fn find_contiguous_range_sum(numbers: &[u128], target: u128) -> Option<(u128, u128)> {
+    let mut left = 0;
+    let mut sum: u128 = 0;
+
+    for (right, &number) in numbers.iter().enumerate() {
+        while left < right && sum.checked_add(number).is_none_or(|next_sum| next_sum > target) {
+            sum -= numbers[left];
+            left += 1;
+        }
+
+        // Either the window is empty or the new sum is known to fit.
+        sum += number;
+        if sum == target && right > left {
+            let range = &numbers[left..=right];
+            return Some((*range.iter().min()?, *range.iter().max()?));
+        }
+    }
+
+    None
+}
+
+
+// Fills the window first, afterwards each new number comes with the validation of the target in front.
+fn add_number<T: Number>(chunk: &mut ChunkResult<T>, window: &mut ScanWindow<T>, new_number: T, new_position: Position) {
+    if window.is_full() {
+        process_next_number(chunk, window, new_number, new_position);
+    } else {
+        window.push(new_number, new_position);
+    }
+}
+
+fn process_next_number<T: Number>(chunk: &mut ChunkResult<T>, window: &mut ScanWindow<T>, new_number: T, new_position: Position) {
+    let (numbers, target_position) = window.numbers();
+    chunk.validate(numbers, target_position);
+    // numbers[1] is the next target, so it leaves the candidates as the new number joins them.
+    if let Some(sorted) = &mut chunk.sorted {
+        sorted.slide(&numbers[1], new_number.clone());
+    }
+    if let Some(window_duplicates) = &mut chunk.window_duplicates {
+        window_duplicates.slide(&numbers[1], new_number.clone());
+    }
+    window.slide(new_number, new_position);
+}
+
+// Numbers of a chunk scan with their positions, the next target in front followed by its candidates. The scan runs
+// backwards, so the newest number at the back is the earliest in the file.
+struct ScanWindow<T> {
+    // Target and candidates.
+    full_len: usize,
+    buffer: ScanBuffer<T>,
+}
+
+enum ScanBuffer<T> {
+    Rotate { numbers: Vec<T>, positions: Vec<Position> },
+    Deque { numbers: VecDeque<T>, positions: VecDeque<Position> },
+}
+
+impl<T: Number> ScanWindow<T> {
+    fn new(item_range_size: usize, window_buffer: WindowBuffer) -> Self {
+        let full_len = item_range_size + 1;
+        let is_deque = match window_buffer {
+            WindowBuffer::Auto => item_range_size >= DEQUE_MIN_ITEM_RANGE_SIZE,
+            WindowBuffer::Rotate => false,
+            WindowBuffer::Deque => true,
+        };
+        // With room for two windows the deque only wraps around once every full_len steps, and only then
+        // make_contiguous has to move it.
+        let buffer = if is_deque {
+            ScanBuffer::Deque { numbers: VecDeque::with_capacity(2 * full_len), positions: VecDeque::with_capacity(2 * full_len) }
+        } else {
+            ScanBuffer::Rotate { numbers: Vec::with_capacity(full_len), positions: Vec::with_capacity(full_len) }
+        };
+        ScanWindow { full_len, buffer }
+    }
+
+    fn is_full(&self) -> bool {
+        let len = match &self.buffer {
+            ScanBuffer::Rotate { numbers, .. } => numbers.len(),
+            ScanBuffer::Deque { numbers, .. } => numbers.len(),
+        };
+        len == self.full_len
+    }
+
+    fn push(&mut self, number: T, position: Position) {
+        match &mut self.buffer {
+            ScanBuffer::Rotate { numbers, positions } => {
+                numbers.push(number);
+                positions.push(position);
+            }
+            ScanBuffer::Deque { numbers, positions } => {
+                numbers.push_back(number);
+                positions.push_back(position);
+            }
+        }
+    }
+
+    // The target followed by its candidates, along with the position of the target.
+    fn numbers(&mut self) -> (&[T], Position) {
+        match &mut self.buffer {
+            ScanBuffer::Rotate { numbers, positions } => (numbers, positions[0]),
+            ScanBuffer::Deque { numbers, positions } => (numbers.make_contiguous(), positions[0]),
+        }
+    }
+
+    // Drops the target, so its first candidate is the next one, and adds the newest number.
+    fn slide(&mut self, number: T, position: Position) {
+        match &mut self.buffer {
+            ScanBuffer::Rotate { numbers, positions } => {
+                numbers.rotate_left(1);
+                numbers[self.full_len - 1] = number;
+                positions.rotate_left(1);
+                positions[self.full_len - 1] = position;
+            }
+            ScanBuffer::Deque { numbers, positions } => {
+                numbers.pop_front();
+                numbers.push_back(number);
+                positions.pop_front();
+                positions.push_back(position);
+            }
+        }
+    }
+}
+
+/// Parses every number of the input in file order.
+pub fn parse_numbers(data: &[u8], config: &Config) -> Result<Vec<u128>, ProcessError> {
+    let config = with_header_window(data, config)?;
+    let config: &Config = &config;
+    let Config { skip_invalid_tokens, skip_header, .. } = *config;
+    let separator = Separator::new(config);
+    let format = NumberFormat::new::<u128>(config)?;
+
+    let mut numbers = Vec::new();
+    let mut offset = 0;
+    for (idx, token) in data.split(|&byte| separator.matches(byte)).enumerate() {
+        let token_offset = offset;
+        offset += token.len() + 1;
+        if idx < skip_header || is_skipped_record(token, config.comment_prefix) {
+            continue;
+        }
+        if let Some(number) = parse_token(token, skip_invalid_tokens, format, || 1 + count_lines(&data[..token_offset], separator))? {
+            numbers.push(number);
+        }
+    }
+    Ok(numbers)
+}
+
+// Malformed tokens abort the chunk unless skip_invalid_tokens is set, in which case they are reported and ignored.
+// The line is only needed for malformed tokens, so it is computed lazily.
+// Over-long tokens are rejected here too, so the stream path agrees with process, where they can't fit the buffer.
+fn parse_token<T: Number>(str_buffer: &[u8], skip_invalid_tokens: bool, format: NumberFormat, line: impl FnOnce() -> usize) -> Result<Option<T>, ProcessError> {
+    if str_buffer.trim_ascii().len() > format.max_len {
+        return reject_token(ProcessError::NumberTooLong { line: line(), max_len: format.max_len }, skip_invalid_tokens);
+    }
+    if let Some(number) = parse_number_from_str_buffer(str_buffer, format) {
+        return Ok(Some(number));
+    }
+
+    reject_token(ProcessError::ParseNumber { bytes: str_buffer.trim_ascii().to_vec(), line: line() }, skip_invalid_tokens)
+}
+
+fn reject_token<T>(err: ProcessError, skip_invalid_tokens: bool) -> Result<Option<T>, ProcessError> {
+    if !skip_invalid_tokens {
+        return Err(err);
+    }
+    warn!("Skipping token: {}", err);
+    Ok(None)
+}
+
+// Surrounding ASCII whitespace is dropped before parsing, which also covers the trailing '\r' of CRLF files.
+// Without Config::crlf bounds still land on the byte before the delimiter, so chunks simply end on the '\r'.
+// Callers pass only the filled part of the buffer, the zero padding on its left is never scanned.
+fn parse_number_from_str_buffer<T: Number>(str_buffer: &[u8], format: NumberFormat) -> Option<T> {
+    T::parse(str_buffer.trim_ascii(), format)
+}
+
+// Digits are accumulated straight from the bytes, so no UTF-8 check is needed and any non-digit byte is rejected.
+fn parse_digits(digits: &[u8], format: NumberFormat) -> Option<u128> {
+    if format.radix == DEFAULT_RADIX && !format.allow_underscores {
+        return parse_decimal(digits);
+    }
+    digit_values(digits, format)?.try_fold(0u128, |number, digit| {
+        number.checked_mul(u128::from(format.radix))?.checked_add(u128::from(digit?))
+    })
+}
+
+// The common case of plain decimal digits, accepting and rejecting the same tokens as u128::from_str once the sign is
+// stripped. With the simd feature whole chunks of digits are parsed at once, the rest goes through a u64 in pieces
+// that can't overflow it, each shifted into the u128 with checked arithmetic so overflow is still caught exactly.
+fn parse_decimal(digits: &[u8]) -> Option<u128> {
+    if digits.is_empty() {
+        return None;
+    }
+    #[cfg(feature = "simd")]
+    let (mut number, rest) = parse_decimal_chunks(digits)?;
+    #[cfg(not(feature = "simd"))]
+    let (mut number, rest) = (0u128, digits);
+    for piece in rest.chunks(U64_DECIMAL_DIGITS) {
+        let mut value: u64 = 0;
+        for &byte in piece {
+            // Bytes below '0' wrap around, so any byte that isn't a digit ends up above 9.
+            let digit = byte.wrapping_sub(b'0');
+            if digit > 9 {
+                return None;
+            }
+            value = value * 10 + u64::from(digit);
+        }
+        number = number.checked_mul(10u128.pow(piece.len() as u32))?.checked_add(u128::from(value))?;
+    }
+    Some(number)
+}
+
+// The leading whole chunks of digits, along with the digits left after them.
+#[cfg(feature = "simd")]
+fn parse_decimal_chunks(digits: &[u8]) -> Option<(u128, &[u8])> {
+    let mut number: u128 = 0;
+    let mut rest = digits;
+    while let Some((chunk, tail)) = rest.split_first_chunk::<DECIMAL_CHUNK_LEN>() {
+        number = number.checked_mul(10u128.pow(DECIMAL_CHUNK_LEN as u32))?.checked_add(u128::from(parse_decimal_chunk(chunk)?))?;
+        rest = tail;
+    }
+    Some((number, rest))
+}
+
+// Digit values are checked in a single compare, then neighbouring lanes are combined into ever wider numbers by two
+// multiply-adds: 16 digits, 8 numbers of 2 digits, 4 of 4, joined into 16 digits as 2 of 8.
+#[cfg(feature = "simd")]
+fn parse_decimal_chunk(chunk: &[u8; DECIMAL_CHUNK_LEN]) -> Option<u64> {
+    let digits = u8x16::new(*chunk) - u8x16::splat(b'0');
+    if !digits.min(u8x16::splat(9)).cmp_eq(digits).all() {
+        return None;
+    }
+    let tens = i16x8::new([10, 1, 10, 1, 10, 1, 10, 1]);
+    let [a, b, c, d] = i16x8::from_u8x16_low(digits).dot(tens).to_array();
+    let [e, f, g, h] = i16x8::from_u8x16_high(digits).dot(tens).to_array();
+    let pairs = i16x8::from_i32x8_truncate(i32x8::new([a, b, c, d, e, f, g, h]));
+    let [first, second, third, fourth] = pairs.dot(i16x8::new([100, 1, 100, 1, 100, 1, 100, 1])).to_array();
+    let high = (first * 10_000 + second) as u64;
+    let low = (third * 10_000 + fourth) as u64;
+    Some(high * 100_000_000 + low)
+}
+
+// Value of each digit after the radix prefix, none for a byte that isn't a digit of the radix.
+// None when the token holds no digit at all.
+fn digit_values(digits: &[u8], NumberFormat { radix, allow_underscores, .. }: NumberFormat) -> Option<impl Iterator<Item = Option<u32>> + '_> {
+    let digits = match radix {
+        2 => digits.strip_prefix(b"0b"),
+        8 => digits.strip_prefix(b"0o"),
+        16 => digits.strip_prefix(b"0x").or_else(|| digits.strip_prefix(b"0X")),
+        _ => None,
+    }.unwrap_or(digits);
+    let is_separator = move |byte: u8| allow_underscores && byte == b'_';
+    if digits.iter().all(|&byte| is_separator(byte)) {
+        return None;
+    }
+    Some(digits.iter().filter(move |&&byte| !is_separator(byte)).map(move |&byte| char::from(byte).to_digit(radix)))
+}
+
+// What ends a record, taken from the Config. Lines end on the delimiter, or on '\n' between whitespace or with crlf.
+#[derive(Clone, Copy)]
+enum Separator {
+    Byte(u8),
+    Whitespace,
+    // Matched on the '\n', the '\r' before it only matters to record_end.
+    Crlf,
+}
+
+impl Separator {
+    fn new(config: &Config) -> Self {
+        if config.whitespace_delimited {
+            Separator::Whitespace
+        } else if config.crlf {
+            Separator::Crlf
+        } else {
+            Separator::Byte(config.delimiter)
+        }
+    }
+
+    fn byte(self) -> Option<u8> {
+        match self {
+            Separator::Byte(delimiter) => Some(delimiter),
+            Separator::Crlf => Some(b'\n'),
+            Separator::Whitespace => None,
+        }
+    }
+
+    fn matches(self, byte: u8) -> bool {
+        match self.byte() {
+            Some(delimiter) => byte == delimiter,
+            None => byte.is_ascii_whitespace(),
+        }
+    }
+
+    fn ends_line(self, byte: u8) -> bool {
+        byte == self.byte().unwrap_or(b'\n')
+    }
+
+    // End of the record the separator at idx closes, exclusive. With Crlf it stops before a '\r' right ahead of the '\n'.
+    fn record_end(self, data: &[u8], idx: usize) -> usize {
+        match self {
+            Separator::Crlf if idx > 0 && data[idx - 1] == b'\r' => idx - 1,
+            _ => idx,
+        }
+    }
+
+    // First byte of the record after the one whose last byte is at end, past the "\r\n" that record_end left out.
+    fn next_record_start(self, data: &[u8], end: usize) -> usize {
+        match self {
+            Separator::Crlf if data.get(end + 1) == Some(&b'\r') => end + 1 + BOUND_TO_NEXT_RECORD,
+            _ => end + BOUND_TO_NEXT_RECORD,
+        }
+    }
+
+    // Offsets of the separating bytes in data.
+    fn positions(self, data: &[u8]) -> Box<dyn Iterator<Item = usize> + '_> {
+        match self.byte() {
+            Some(delimiter) => Box::new(memchr_iter(delimiter, data)),
+            None => Box::new(data.iter().enumerate().filter(|(_, byte)| byte.is_ascii_whitespace()).map(|(idx, _)| idx)),
+        }
+    }
+
+    // Same as BufRead::read_until, stopping after whichever byte matches.
+    fn read_record(self, reader: &mut impl BufRead, record: &mut Vec<u8>) -> io::Result<usize> {
+        if let Some(delimiter) = self.byte() {
+            return reader.read_until(delimiter, record);
+        }
+        let mut read = 0;
+        loop {
+            let available = match reader.fill_buf() {
+                Ok(available) => available,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            let (found, used) = match available.iter().position(|&byte| self.matches(byte)) {
+                Some(idx) => (true, idx + 1),
+                None => (false, available.len()),
+            };
+            record.extend_from_slice(&available[..used]);
+            reader.consume(used);
+            read += used;
+            if found || used == 0 {
+                return Ok(read);
+            }
+        }
+    }
+}
+
+// How number tokens are spelled, taken from the Config.
+#[derive(Clone, Copy)]
+struct NumberFormat {
+    radix: u32,
+    allow_underscores: bool,
+    // Longest token without surrounding whitespace, see max_token_len.
+    max_len: usize,
+}
+
+impl NumberFormat {
+    fn new<T: Number>(config: &Config) -> Result<Self, ProcessError> {
+        if !(MIN_RADIX..=MAX_RADIX).contains(&config.radix) {
+            return Err(ProcessError::InvalidRadix(config.radix));
+        }
+        let max_len = max_token_len(T::max_digits(config.radix), config.allow_underscores);
+        Ok(NumberFormat { radix: config.radix, allow_underscores: config.allow_underscores, max_len })
+    }
+}
+
+// Digits plus a sign and a two-byte prefix. Separators are budgeted at one per digit, longer tokens don't fit.
+fn max_token_len(digits: usize, allow_underscores: bool) -> usize {
+    if allow_underscores {
+        digits * 2 + 3
+    } else {
+        digits + 3
+    }
+}
+
+// Digits of u128::MAX in the radix.
+fn u128_digits(radix: u32) -> usize {
+    let mut digits = 0;
+    let mut remaining = u128::MAX;
+    while remaining > 0 {
+        remaining /= u128::from(radix);
+        digits += 1;
+    }
+    digits
+}
+
+
+// Numeric types the chunks can be scanned as, each with its own validity check.
+trait Number: Clone + Default + Ord + Send + Sync {
+    // None when the bytes are not a number in range, leading '+' being accepted as with from_str.
+    fn parse(bytes: &[u8], format: NumberFormat) -> Option<Self>;
+    // Most digits a number may have in the radix, which sizes the token buffer.
+    fn max_digits(radix: u32) -> usize;
+    // Adds the pair checks of the search to comparisons, see Config::count_comparisons.
+    fn find_addends(target: &Self, candidates: &[Self], search: PairSearch, comparisons: &mut u64) -> Option<(Self, Self)>;
+    // Same as find_addends over candidates sorted in ascending order.
+    fn find_sorted_addends(target: &Self, sorted: &[Self], search: PairSearch, comparisons: &mut u64) -> Option<(Self, Self)>;
+    // Whether first + second is target, a sum overflowing the type never is.
+    fn is_sum(target: &Self, first: &Self, second: &Self, search: PairSearch) -> bool;
+}
+
+// Candidates of the window in ascending order, see Config::sorted_window. Sorted on the first search only, afterwards
+// each slide of the window shifts just the numbers between the outgoing and the incoming one.
+#[derive(Default)]
+struct SortedWindow<T> {
+    numbers: Vec<T>,
+}
+
+impl<T: Number> SortedWindow<T> {
+    fn find_addends(&mut self, target: &T, candidates: &[T], search: PairSearch, comparisons: &mut u64) -> Option<(T, T)> {
+        if self.numbers.is_empty() {
+            self.numbers.extend_from_slice(candidates);
+            self.numbers.sort_unstable();
+        }
+        T::find_sorted_addends(target, &self.numbers, search, comparisons)
+    }
+
+    // Replaces one occurrence of outgoing, which must be in the window, with incoming.
+    fn slide(&mut self, outgoing: &T, incoming: T) {
+        let from = self.numbers.binary_search(outgoing).expect("outgoing number is in the sorted window");
+        let to = self.numbers.partition_point(|number| *number < incoming);
+        if to > from {
+            self.numbers[from..to].rotate_left(1);
+            self.numbers[to - 1] = incoming;
+        } else {
+            self.numbers[to..=from].rotate_right(1);
+            self.numbers[to] = incoming;
+        }
+    }
+}
+
+// Occurrences of each candidate value of the window, see Config::report_dupes. Counted on the first check only,
+// afterwards each slide of the window updates just the outgoing and the incoming value.
+#[derive(Default)]
+struct WindowDuplicates<T> {
+    counts: BTreeMap<T, usize>,
+    // Values counted more than once.
+    repeated: usize,
+}
+
+impl<T: Number> WindowDuplicates<T> {
+    fn repeated(&mut self, candidates: &[T]) -> usize {
+        if self.counts.is_empty() {
+            candidates.iter().for_each(|number| self.add(number.clone()));
+        }
+        self.repeated
+    }
+
+    // Replaces one occurrence of outgoing, which must be in the window, with incoming.
+    fn slide(&mut self, outgoing: &T, incoming: T) {
+        let count = self.counts.get_mut(outgoing).expect("outgoing number is in the window");
+        *count -= 1;
+        match *count {
+            0 => {
+                self.counts.remove(outgoing);
+            }
+            1 => self.repeated -= 1,
+            _ => {}
+        }
+        self.add(incoming);
+    }
+
+    fn add(&mut self, number: T) {
+        let count = self.counts.entry(number).or_insert(0);
+        *count += 1;
+        if *count == 2 {
+            self.repeated += 1;
+        }
+    }
+}
+
+// How pairs are searched, taken from the Config. Pairs with a 0 addend are only considered when zero_pairs is set.
+// Epsilon only applies to floats.
+#[derive(Clone, Copy)]
+struct PairSearch {
+    zero_pairs: bool,
+    epsilon: f64,
+    lookup: PairLookup,
+    #[cfg(parallel)]
+    parallel: bool,
+}
+
+impl PairSearch {
+    fn new(config: &Config) -> Self {
+        PairSearch {
+            zero_pairs: !config.no_zero_pair,
+            epsilon: config.epsilon,
+            lookup: config.pair_lookup,
+            #[cfg(parallel)]
+            parallel: config.parallel_pair_search,
+        }
+    }
+
+    // Search of the public validity checks.
+    fn sequential() -> Self {
+        PairSearch {
+            zero_pairs: true,
+            epsilon: DEFAULT_EPSILON,
+            lookup: PairLookup::Auto,
+            #[cfg(parallel)]
+            parallel: false,
+        }
+    }
+
+    // The hashset pays off only once the quadratic scan gets expensive, so Auto keeps the nested loop for small windows.
+    fn uses_hashset(self, candidates: usize) -> bool {
+        match self.lookup {
+            PairLookup::Auto => candidates >= HASHSET_MIN_ITEM_RANGE_SIZE,
+            PairLookup::Nested => false,
+            PairLookup::Hashset => true,
+        }
+    }
+
+    #[cfg(parallel)]
+    fn splits(self, candidates: usize) -> bool {
+        self.parallel && candidates >= PARALLEL_MIN_ITEM_RANGE_SIZE
+    }
+}
+
+impl Number for u128 {
+    fn parse(bytes: &[u8], format: NumberFormat) -> Option<Self> {
+        parse_digits(bytes.strip_prefix(b"+").unwrap_or(bytes), format)
+    }
+
+    fn max_digits(radix: u32) -> usize {
+        u128_digits(radix)
+    }
+
+    fn find_addends(target: &Self, candidates: &[Self], search: PairSearch, comparisons: &mut u64) -> Option<(Self, Self)> {
+        find_unsigned_addends(*target, candidates, search, comparisons)
+    }
+
+    fn find_sorted_addends(target: &Self, sorted: &[Self], search: PairSearch, comparisons: &mut u64) -> Option<(Self, Self)> {
+        is_number_valid_sorted(*target, sorted, search.zero_pairs, comparisons)
+    }
+
+    fn is_sum(target: &Self, first: &Self, second: &Self, _search: PairSearch) -> bool {
+        first.checked_add(*second) == Some(*target)
+    }
+}
+
+impl Number for i128 {
+    // The magnitude is parsed unsigned so i128::MIN, whose magnitude doesn't fit an i128, still parses.
+    fn parse(bytes: &[u8], format: NumberFormat) -> Option<Self> {
+        match bytes.strip_prefix(b"-") {
+            Some(digits) => 0i128.checked_sub_unsigned(parse_digits(digits, format)?),
+            None => i128::try_from(parse_digits(bytes.strip_prefix(b"+").unwrap_or(bytes), format)?).ok(),
+        }
+    }
+
+    fn max_digits(radix: u32) -> usize {
+        u128_digits(radix)
+    }
+
+    fn find_addends(target: &Self, candidates: &[Self], search: PairSearch, comparisons: &mut u64) -> Option<(Self, Self)> {
+        find_signed_addends(*target, candidates, search, comparisons)
+    }
+
+    fn find_sorted_addends(target: &Self, sorted: &[Self], search: PairSearch, comparisons: &mut u64) -> Option<(Self, Self)> {
+        is_signed_number_valid_sorted(*target, sorted, search.zero_pairs, comparisons)
+    }
+
+    fn is_sum(target: &Self, first: &Self, second: &Self, _search: PairSearch) -> bool {
+        first.checked_add(*second) == Some(*target)
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl Number for BigUint {
+    fn parse(bytes: &[u8], format: NumberFormat) -> Option<Self> {
+        let digits = digit_values(bytes.strip_prefix(b"+").unwrap_or(bytes), format)?;
+        let digits = digits.map(|digit| digit.map(|digit| digit as u8)).collect::<Option<Vec<u8>>>()?;
+        BigUint::from_radix_be(&digits, format.radix)
+    }
+
+    fn max_digits(_radix: u32) -> usize {
+        BIGINT_MAX_DIGITS
+    }
+
+    fn find_addends(target: &Self, candidates: &[Self], search: PairSearch, comparisons: &mut u64) -> Option<(Self, Self)> {
+        is_big_number_valid(target, candidates, search.zero_pairs, comparisons)
+    }
+
+    fn find_sorted_addends(target: &Self, sorted: &[Self], search: PairSearch, comparisons: &mut u64) -> Option<(Self, Self)> {
+        is_big_number_valid_sorted(target, sorted, search.zero_pairs, comparisons)
+    }
+
+    fn is_sum(target: &Self, first: &Self, second: &Self, _search: PairSearch) -> bool {
+        first + second == *target
+    }
+}
+
+// Underscores are dropped before parsing, f64's own parser doesn't accept them. A -0 is parsed as 0 so zero pairs and
+// Config::no_zero_pair see a single zero.
+impl Number for Float {
+    fn parse(bytes: &[u8], format: NumberFormat) -> Option<Self> {
+        let bytes = if format.allow_underscores && bytes.contains(&b'_') {
+            if bytes.iter().all(|&byte| byte == b'_') {
+                return None;
+            }
+            Cow::Owned(bytes.iter().copied().filter(|&byte| byte != b'_').collect())
+        } else {
+            Cow::Borrowed(bytes)
+        };
+        let value: f64 = std::str::from_utf8(&bytes).ok()?.parse().ok()?;
+        value.is_finite().then_some(Float(value + 0.0))
+    }
+
+    fn max_digits(_radix: u32) -> usize {
+        FLOAT_MAX_DIGITS
+    }
+
+    fn find_addends(target: &Self, candidates: &[Self], search: PairSearch, comparisons: &mut u64) -> Option<(Self, Self)> {
+        #[cfg(parallel)]
+        if search.splits(candidates.len()) {
+            return is_float_valid_parallel(*target, candidates, search, comparisons);
+        }
+        is_float_valid_nested(*target, candidates, search, comparisons)
+    }
+
+    fn find_sorted_addends(target: &Self, sorted: &[Self], search: PairSearch, comparisons: &mut u64) -> Option<(Self, Self)> {
+        is_float_valid_sorted(*target, sorted, search, comparisons)
+    }
+
+    fn is_sum(target: &Self, first: &Self, second: &Self, search: PairSearch) -> bool {
+        float_sums_to(*target, *first, *second, search.epsilon)
+    }
+}
+
+/// The first pair of candidates at distinct positions summing to target, none when the target is invalid. A single
+/// candidate never pairs with itself, so [5] doesn't validate 10 while [5, 5] does.
+/// A 0 candidate pairs with a candidate equal to the target, see Config::no_zero_pair to reject that.
+pub fn is_number_valid(target: u128, candidates: &[u128]) -> Option<(u128, u128)> {
+    find_unsigned_addends(target, candidates, PairSearch::sequential(), &mut 0)
+}
+
+/// Same as is_number_valid with the pair search of config, e.g. its Config::pair_lookup or Config::no_zero_pair.
+pub fn is_number_valid_with(target: u128, candidates: &[u128], config: &Config) -> Option<(u128, u128)> {
+    find_unsigned_addends(target, candidates, PairSearch::new(config), &mut 0)
+}
+
+fn find_unsigned_addends(target: u128, candidates: &[u128], search: PairSearch, comparisons: &mut u64) -> Option<(u128, u128)> {
+    #[cfg(parallel)]
+    if search.splits(candidates.len()) {
+        return is_number_valid_parallel(target, candidates, search.zero_pairs, comparisons);
+    }
+    if search.uses_hashset(candidates.len()) {
+        is_number_valid_hashset(target, candidates, search.zero_pairs, comparisons)
+    } else {
+        is_number_valid_nested(target, candidates, search.zero_pairs, comparisons)
+    }
+}
+
+// Each candidate looks up its complement among the candidates seen before it, so only distinct positions are paired
+// and c == target - c needs c to appear twice. Without zero pairs a 0 is never inserted, so it can't be a complement.
+fn is_number_valid_hashset(target: u128, candidates: &[u128], zero_pairs: bool, comparisons: &mut u64) -> Option<(u128, u128)> {
+    let mut seen = HashSet::with_capacity(candidates.len());
+    for &candidate in candidates.iter().filter(|&&candidate| candidate <= target && (zero_pairs || candidate != 0)) {
+        *comparisons += 1;
+        let complement = target - candidate;
+        if seen.contains(&complement) {
+            return Some((complement, candidate));
+        }
+        seen.insert(candidate);
+    }
+    None
+}
+
+// Each candidate is paired only with the ones after it, so no candidate is ever paired with itself and each pair of
+// positions is tried once. A value repeated in the window still pairs with its other occurrence.
+// Skip all numbers greater than the target (excluding the target itself). The target and 0 may still be valid candidates
+// together unless zero pairs are rejected. Sums overflowing u128 can never equal the target, so they are rejected instead of wrapping.
+// The inner candidates checked are counted once per outer candidate, which keeps the inner loop as it is.
+fn is_number_valid_nested(target: u128, candidates: &[u128], zero_pairs: bool, comparisons: &mut u64) -> Option<(u128, u128)> {
+    for (outer_idx, &outer) in candidates.iter().enumerate() {
+        if outer > target || (!zero_pairs && outer == 0) {
+            continue;
+        }
+        let after_outer = &candidates[outer_idx + 1..];
+        let found = after_outer.iter().position(|&inner| (zero_pairs || inner != 0) && inner.checked_add(outer) == Some(target));
+        *comparisons += checked_count(found, after_outer.len());
+        if let Some(inner_idx) = found {
+            return Some((outer, after_outer[inner_idx]));
+        }
+    }
+    None
+}
+
+// Candidates a linear search went through, up to the match or all of them.
+fn checked_count(found: Option<usize>, len: usize) -> u64 {
+    found.map_or(len, |pos| pos + 1) as u64
+}
+
+// Same as the nested loop with the outer candidates spread over rayon's pool. find_map_first stops the candidates
+// after a match and keeps the pair the nested loop would return.
+// Outer candidates past the match that already ran on other threads are counted as well.
+#[cfg(parallel)]
+fn is_number_valid_parallel(target: u128, candidates: &[u128], zero_pairs: bool, comparisons: &mut u64) -> Option<(u128, u128)> {
+    let checked = AtomicU64::new(0);
+    let addends = candidates.par_iter()
+        .enumerate()
+        .filter(|(_, &outer_ref)| outer_ref <= target && (zero_pairs || outer_ref != 0))
+        .find_map_first(|(idx, &outer_ref)| {
+            let inner = &candidates[idx + 1..];
+            let found = inner.iter().position(|&inner_ref| (zero_pairs || inner_ref != 0) && inner_ref.checked_add(outer_ref) == Some(target));
+            checked.fetch_add(checked_count(found, inner.len()), Ordering::Relaxed);
+            found.map(|pos| (outer_ref, inner[pos]))
+        });
+    *comparisons += checked.into_inner();
+    addends
+}
+
+/// Same as is_number_valid for candidates sorted in ascending order, the pair is returned smallest addend first.
+pub fn is_number_valid_in_sorted(target: u128, sorted_candidates: &[u128]) -> Option<(u128, u128)> {
+    is_number_valid_sorted(target, sorted_candidates, true, &mut 0)
+}
+
+// Each candidate only searches the candidates sorted after it, so a number pairs with itself only when it appears twice.
+// Once the complement sorts before the candidate every pair has been tried, which also stops before candidates above
+// the target. Without zero pairs a 0 complement only comes up for a 0 target, whose 0 candidates are skipped.
+// Each binary search counts as a single check, as a hashset lookup does.
+fn is_number_valid_sorted(target: u128, sorted: &[u128], zero_pairs: bool, comparisons: &mut u64) -> Option<(u128, u128)> {
+    sorted.iter()
+        .enumerate()
+        .take_while(|(_, &candidate)| candidate <= target && target - candidate >= candidate)
+        .filter(|(_, &candidate)| zero_pairs || candidate != 0)
+        .find_map(|(idx, &candidate)| {
+            *comparisons += 1;
+            let complement = target - candidate;
+            sorted[idx + 1..].binary_search(&complement).ok().map(|_| (candidate, complement))
+        })
+}
+
+
+/// Same as is_number_valid for candidates which may be negative.
+pub fn is_signed_number_valid(target: i128, candidates: &[i128]) -> Option<(i128, i128)> {
+    find_signed_addends(target, candidates, PairSearch::sequential(), &mut 0)
+}
+
+// A negative candidate can pair with one greater than the target, so unlike the unsigned check only zeros may be skipped.
+fn find_signed_addends(target: i128, candidates: &[i128], search: PairSearch, comparisons: &mut u64) -> Option<(i128, i128)> {
+    #[cfg(parallel)]
+    if search.splits(candidates.len()) {
+        return is_signed_number_valid_parallel(target, candidates, search.zero_pairs, comparisons);
+    }
+    if search.uses_hashset(candidates.len()) {
+        is_signed_number_valid_hashset(target, candidates, search.zero_pairs, comparisons)
+    } else {
+        is_signed_number_valid_nested(target, candidates, search.zero_pairs, comparisons)
+    }
+}
+
+// A complement outside the i128 range can't be among the candidates.
+fn is_signed_number_valid_hashset(target: i128, candidates: &[i128], zero_pairs: bool, comparisons: &mut u64) -> Option<(i128, i128)> {
+    let mut seen = HashSet::with_capacity(candidates.len());
+    for &candidate in candidates.iter().filter(|&&candidate| zero_pairs || candidate != 0) {
+        *comparisons += 1;
+        if let Some(complement) = target.checked_sub(candidate).filter(|complement| seen.contains(complement)) {
+            return Some((complement, candidate));
+        }
+        seen.insert(candidate);
+    }
+    None
+}
+
+fn is_signed_number_valid_nested(target: i128, candidates: &[i128], zero_pairs: bool, comparisons: &mut u64) -> Option<(i128, i128)> {
+    for (outer_idx, &outer) in candidates.iter().enumerate() {
+        if !zero_pairs && outer == 0 {
+            continue;
+        }
+        let after_outer = &candidates[outer_idx + 1..];
+        let found = after_outer.iter().position(|&inner| (zero_pairs || inner != 0) && inner.checked_add(outer) == Some(target));
+        *comparisons += checked_count(found, after_outer.len());
+        if let Some(inner_idx) = found {
+            return Some((outer, after_outer[inner_idx]));
+        }
+    }
+    None
+}
+
+#[cfg(parallel)]
+fn is_signed_number_valid_parallel(target: i128, candidates: &[i128], zero_pairs: bool, comparisons: &mut u64) -> Option<(i128, i128)> {
+    let checked = AtomicU64::new(0);
+    let addends = candidates.par_iter()
+        .enumerate()
+        .filter(|(_, &outer_ref)| zero_pairs || outer_ref != 0)
+        .find_map_first(|(idx, &outer_ref)| {
+            let inner = &candidates[idx + 1..];
+            let found = inner.iter().position(|&inner_ref| (zero_pairs || inner_ref != 0) && inner_ref.checked_add(outer_ref) == Some(target));
+            checked.fetch_add(checked_count(found, inner.len()), Ordering::Relaxed);
+            found.map(|pos| (outer_ref, inner[pos]))
+        });
+    *comparisons += checked.into_inner();
+    addends
+}
+
+// A complement above i128::MAX can't be among the candidates, but a larger candidate may still have one in range,
+// while one below i128::MIN means every larger candidate's complement sorts before it.
+fn is_signed_number_valid_sorted(target: i128, sorted: &[i128], zero_pairs: bool, comparisons: &mut u64) -> Option<(i128, i128)> {
+    for (idx, &candidate) in sorted.iter().enumerate() {
+        let complement = match target.checked_sub(candidate) {
+            Some(complement) if complement < candidate => break,
+            Some(complement) => complement,
+            None if candidate < 0 => continue,
+            None => break,
+        };
+        if !zero_pairs && (candidate == 0 || complement == 0) {
+            continue;
+        }
+        *comparisons += 1;
+        if sorted[idx + 1..].binary_search(&complement).is_ok() {
+            return Some((candidate, complement));
+        }
+    }
+    None
+}
+
+
+// Same as the u128 hashset check at any window size, the nested loop would allocate a sum per pair.
+// The candidates are borrowed rather than cloned into the set.
+#[cfg(feature = "bigint")]
+fn is_big_number_valid(target: &BigUint, candidates: &[BigUint], zero_pairs: bool, comparisons: &mut u64) -> Option<(BigUint, BigUint)> {
+    let mut seen = HashSet::with_capacity(candidates.len());
+    for candidate in candidates.iter().filter(|&candidate| candidate <= target && (zero_pairs || *candidate != BigUint::ZERO)) {
+        *comparisons += 1;
+        let complement = target - candidate;
+        if seen.contains(&complement) {
+            return Some((complement, candidate.clone()));
+        }
+        seen.insert(candidate);
+    }
+    None
+}
+
+// Same as the u128 sorted check.
+#[cfg(feature = "bigint")]
+fn is_big_number_valid_sorted(target: &BigUint, sorted: &[BigUint], zero_pairs: bool, comparisons: &mut u64) -> Option<(BigUint, BigUint)> {
+    for (idx, candidate) in sorted.iter().enumerate() {
+        if candidate > target {
+            break;
+        }
+        let complement = target - candidate;
+        if complement < *candidate {
+            break;
+        }
+        if !zero_pairs && *candidate == BigUint::ZERO {
+            continue;
+        }
+        *comparisons += 1;
+        if sorted[idx + 1..].binary_search(&complement).is_ok() {
+            return Some((candidate.clone(), complement));
+        }
+    }
+    None
+}
+
+
+// Every path of the float search checks pairs through this, so they agree on sums right at the tolerance.
+fn float_sums_to(target: Float, first: Float, second: Float, epsilon: f64) -> bool {
+    (first.0 + second.0 - target.0).abs() <= epsilon
+}
+
+// Negative candidates can pair with ones above the target, so none are skipped but zeros without zero pairs.
+fn is_float_valid_nested(target: Float, candidates: &[Float], search: PairSearch, comparisons: &mut u64) -> Option<(Float, Float)> {
+    let is_addend = |candidate: &Float| search.zero_pairs || candidate.0 != 0.0;
+    for (outer_idx, &outer) in candidates.iter().enumerate() {
+        if !is_addend(&outer) {
+            continue;
+        }
+        let after_outer = &candidates[outer_idx + 1..];
+        let found = after_outer.iter().position(|&inner| is_addend(&inner) && float_sums_to(target, outer, inner, search.epsilon));
+        *comparisons += checked_count(found, after_outer.len());
+        if let Some(inner_idx) = found {
+            return Some((outer, after_outer[inner_idx]));
+        }
+    }
+    None
+}
+
+#[cfg(parallel)]
+fn is_float_valid_parallel(target: Float, candidates: &[Float], search: PairSearch, comparisons: &mut u64) -> Option<(Float, Float)> {
+    let is_addend = |candidate: &Float| search.zero_pairs || candidate.0 != 0.0;
+    let checked = AtomicU64::new(0);
+    let addends = candidates.par_iter()
+        .enumerate()
+        .filter(|(_, outer_ref)| is_addend(outer_ref))
+        .find_map_first(|(idx, &outer_ref)| {
+            let inner = &candidates[idx + 1..];
+            let found = inner.iter().position(|&inner_ref| is_addend(&inner_ref) && float_sums_to(target, outer_ref, inner_ref, search.epsilon));
+            checked.fetch_add(checked_count(found, inner.len()), Ordering::Relaxed);
+            found.map(|pos| (outer_ref, inner[pos]))
+        });
+    *comparisons += checked.into_inner();
+    addends
+}
+
+// Each candidate looks for its complement within epsilon among the candidates sorted after it. The bounds of that range
+// are rounded, so they are widened by a few ulps of the operands and every candidate in them is checked exactly.
+// Once the range ends below the candidate every pair has been tried. Each number checked in a range counts once.
+fn is_float_valid_sorted(target: Float, sorted: &[Float], search: PairSearch, comparisons: &mut u64) -> Option<(Float, Float)> {
+    for (idx, &candidate) in sorted.iter().enumerate() {
+        let complement = target.0 - candidate.0;
+        let slack = search.epsilon + (target.0.abs() + candidate.0.abs()) * f64::EPSILON * 4.0;
+        if complement + slack < candidate.0 {
+            break;
+        }
+        if !search.zero_pairs && candidate.0 == 0.0 {
+            continue;
+        }
+        let rest = &sorted[idx + 1..];
+        let from = rest.partition_point(|number| number.0 < complement - slack);
+        let in_range = rest[from..].iter().take_while(|number| number.0 <= complement + slack).count();
+        let found = rest[from..from + in_range].iter()
+            .position(|&number| (search.zero_pairs || number.0 != 0.0) && float_sums_to(target, candidate, number, search.epsilon));
+        *comparisons += checked_count(found, in_range);
+        if let Some(pos) = found {
+            return Some((candidate, rest[from + pos]));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Small values make pairs summing to the target likely, any value covers sums overflowing near the maximum.
+    fn unsigned_value() -> impl Strategy<Value = u128> {
+        prop_oneof![0..64u128, (u128::MAX - 64)..=u128::MAX, any::<u128>()]
+    }
+
+    fn signed_value() -> impl Strategy<Value = i128> {
+        prop_oneof![-64..64i128, i128::MIN..=i128::MIN + 64, (i128::MAX - 64)..=i128::MAX, any::<i128>()]
+    }
+
+    // Windows on both sides of the hashset threshold.
+    fn window_len() -> impl Strategy<Value = usize> {
+        prop_oneof![0..HASHSET_MIN_ITEM_RANGE_SIZE, HASHSET_MIN_ITEM_RANGE_SIZE..HASHSET_MIN_ITEM_RANGE_SIZE * 2]
+    }
+
+    fn brute_force_valid(target: u128, candidates: &[u128]) -> bool {
+        (0..candidates.len()).any(|i| (i + 1..candidates.len()).any(|j| candidates[i].checked_add(candidates[j]) == Some(target)))
+    }
+
+    fn brute_force_signed_valid(target: i128, candidates: &[i128]) -> bool {
+        (0..candidates.len()).any(|i| (i + 1..candidates.len()).any(|j| candidates[i].checked_add(candidates[j]) == Some(target)))
+    }
+
+    // The pair sums to the target and takes two distinct positions of the candidates.
+    fn is_pair_of<T: Copy + PartialEq>(candidates: &[T], (first, second): (T, T)) -> bool {
+        let count = |value: T| candidates.iter().filter(|&&candidate| candidate == value).count();
+        if first == second { count(first) >= 2 } else { count(first) >= 1 && count(second) >= 1 }
+    }
+
+    proptest! {
+        #[test]
+        fn is_number_valid_matches_brute_force(
+            candidates in window_len().prop_flat_map(|len| prop::collection::vec(unsigned_value(), len)),
+            target in unsigned_value(),
+        ) {
+            let found = is_number_valid(target, &candidates);
+            prop_assert_eq!(found.is_some(), brute_force_valid(target, &candidates));
+            if let Some((first, second)) = found {
+                prop_assert_eq!(first.checked_add(second), Some(target));
+                prop_assert!(is_pair_of(&candidates, (first, second)));
+            }
+        }
+
+        #[test]
+        fn is_number_valid_in_sorted_matches_brute_force(
+            mut candidates in window_len().prop_flat_map(|len| prop::collection::vec(unsigned_value(), len)),
+            target in unsigned_value(),
+        ) {
+            candidates.sort_unstable();
+            let found = is_number_valid_in_sorted(target, &candidates);
+            prop_assert_eq!(found.is_some(), brute_force_valid(target, &candidates));
+            if let Some((first, second)) = found {
+                prop_assert!(first <= second);
+                prop_assert_eq!(first.checked_add(second), Some(target));
+                prop_assert!(is_pair_of(&candidates, (first, second)));
+            }
+        }
+
+        #[test]
+        fn is_signed_number_valid_matches_brute_force(
+            candidates in window_len().prop_flat_map(|len| prop::collection::vec(signed_value(), len)),
+            target in signed_value(),
+        ) {
+            let found = is_signed_number_valid(target, &candidates);
+            prop_assert_eq!(found.is_some(), brute_force_signed_valid(target, &candidates));
+            if let Some((first, second)) = found {
+                prop_assert_eq!(first.checked_add(second), Some(target));
+                prop_assert!(is_pair_of(&candidates, (first, second)));
+            }
+        }
+    }
+
+    fn right_bounds(data: &[u8], ini_pos: usize, item_range_size: usize) -> (usize, usize) {
+        get_right_bounds(data, ini_pos, item_range_size, Separator::Byte(b'\n'), |record| !is_skipped_record(record, None))
+    }
+
+    #[test]
+    fn right_bounds_with_a_single_delimiter_after_ini_pos() {
+        // The overflow defaults to the end of the undelimited last record.
+        assert_eq!(right_bounds(b"1\n2", 0, 3), (0, 2));
+        assert_eq!(right_bounds(b"1\n2\n3\n", 2, 3), (2, 4));
+    }
+
+    #[test]
+    fn right_bounds_with_window_delimiters_after_ini_pos() {
+        // One short of the window + 1 delimiters the overflow needs, so it also defaults to the end of the last record.
+        assert_eq!(right_bounds(b"1\n2\n3\n", 0, 3), (0, 4));
+        assert_eq!(right_bounds(b"1\n2\n3\n4", 0, 3), (0, 6));
+    }
+
+    #[test]
+    fn right_bounds_with_more_than_window_delimiters_after_ini_pos() {
+        assert_eq!(right_bounds(b"1\n2\n3\n4\n5\n6\n", 0, 3), (0, 6));
+        assert_eq!(right_bounds(b"1\n2\n3\n4\n5\n6\n", 3, 3), (2, 8));
+        assert_eq!(right_bounds(b"10\n20\n30\n40\n50\n", 1, 2), (1, 7));
+    }
+
+    #[test]
+    fn right_bounds_with_ini_pos_on_the_last_byte() {
+        // The final delimiter only ends the last record, both bounds are the byte before it.
+        assert_eq!(right_bounds(b"1\n2\n3\n", 5, 3), (4, 4));
+        // In an undelimited last record both default to its end.
+        assert_eq!(right_bounds(b"1\n2\n34", 5, 3), (5, 5));
+    }
+
+    fn lines<T: fmt::Display>(numbers: &[T]) -> Vec<u8> {
+        numbers.iter().map(|number| format!("{}\n", number)).collect::<String>().into_bytes()
+    }
+
+    fn invalid_values(data: &[u8], parallelism: usize, item_range_size: usize) -> Vec<u128> {
+        let config = Config::builder().parallelism(parallelism).window(item_range_size).build();
+        find_invalid_numbers_with_config(data, &config).unwrap().into_iter().map(|invalid| invalid.value).collect()
+    }
+
+    proptest! {
+        #[test]
+        fn chunked_scan_matches_reference(
+            numbers in prop::collection::vec(0..40u128, 1..200),
+            parallelism in 1..16usize,
+            item_range_size in 1..10usize,
+        ) {
+            let data = lines(&numbers);
+            prop_assert_eq!(invalid_values(&data, parallelism, item_range_size), find_invalid_numbers_reference(&data, item_range_size).unwrap());
+        }
+    }
+
+    #[test]
+    fn chunked_scan_matches_reference_on_challenge_input() {
+        let data = include_bytes!("../resources/challenge_input.txt");
+        let reference = find_invalid_numbers_reference(data, DEFAULT_ITEM_RANGE_SIZE).unwrap();
+        for parallelism in [1, 2, 3, 4, 7, 16] {
+            assert_eq!(invalid_values(data, parallelism, DEFAULT_ITEM_RANGE_SIZE), reference, "parallelism {}", parallelism);
+        }
+    }
+
+    // The byte loop get_right_bounds replaced, with its check for blank lines widened to any record the closure rejects.
+    fn right_bounds_byte_loop(data: &[u8], ini_pos: usize, item_range_size: usize, fills_window: impl Fn(&[u8]) -> bool) -> (usize, usize) {
+        let mut right_bound = if data.ends_with(b"\n") { data.len() - 2 } else { data.len() - 1 };
+        let mut right_bound_overflow = right_bound;
+        let (mut idx, mut record_start, mut overflow_count) = (ini_pos, ini_pos, 0);
+        while idx < data.len() && overflow_count <= item_range_size {
+            if data[idx] == b'\n' {
+                let record = &data[record_start..idx];
+                record_start = idx + 1;
+                if idx > 0 && (overflow_count == 0 || fills_window(record)) {
+                    overflow_count += 1;
+                    if overflow_count == 1 {
+                        right_bound = idx - 1;
+                    }
+                    if overflow_count == item_range_size + 1 {
+                        right_bound_overflow = idx - 1;
+                    }
+                }
+            }
+            idx += 1;
+        }
+        (right_bound, right_bound_overflow)
+    }
+
+    proptest! {
+        // Cheap cases over a small alphabet, so many of them.
+        #![proptest_config(ProptestConfig::with_cases(4096))]
+        #[test]
+        fn right_bounds_match_byte_loop(
+            (data, ini_pos) in prop::collection::vec(prop::sample::select(b"0123 #\n\n\n".to_vec()), 1..80)
+                .prop_filter("holds a number", |data| data.iter().any(u8::is_ascii_digit))
+                .prop_flat_map(|data| { let len = data.len(); (Just(data), 0..len) }),
+            item_range_size in 1..6usize,
+        ) {
+            let fills_window = |record: &[u8]| !is_skipped_record(record, Some(b'#'));
+            let separator = Separator::Byte(b'\n');
+            prop_assert_eq!(
+                get_right_bounds(&data, ini_pos, item_range_size, separator, fills_window),
+                right_bounds_byte_loop(&data, ini_pos, item_range_size, fills_window),
+            );
+        }
+    }
+
+    fn from_str_u128(token: &[u8]) -> Option<u128> {
+        std::str::from_utf8(token).ok()?.parse().ok()
+    }
+
+    // Runs of any length up to past the digits of u128::MAX, so whole SIMD chunks, the pieces after them and overflow
+    // are all covered, along with stray non-digit bytes.
+    fn decimal_token() -> impl Strategy<Value = Vec<u8>> {
+        prop_oneof![
+            any::<u128>().prop_map(|number| number.to_string().into_bytes()),
+            (any::<u128>(), 0..8usize).prop_map(|(number, zeros)| format!("{}{}", "0".repeat(zeros), number).into_bytes()),
+            prop::collection::vec(b'0'..=b'9', 0..48),
+            prop::collection::vec(prop::sample::select(b"0123456789/:a- ".to_vec()), 0..48),
+        ]
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4096))]
+        #[test]
+        fn parse_decimal_matches_from_str(token in decimal_token()) {
+            prop_assert_eq!(parse_decimal(&token), from_str_u128(&token));
+        }
+    }
+
+    #[test]
+    fn parse_decimal_at_the_edges_of_u128() {
+        let max = u128::MAX.to_string();
+        for token in [max.clone(), format!("000{}", max), (u128::MAX / 10).to_string(), "340282366920938463463374607431768211456".to_string(), format!("{}0", max), "0".repeat(40)] {
+            assert_eq!(parse_decimal(token.as_bytes()), from_str_u128(token.as_bytes()), "{}", token);
+        }
+    }
+
+    fn with_crlf(data: &[u8]) -> Vec<u8> {
+        String::from_utf8_lossy(data).replace('\n', "\r\n").into_bytes()
+    }
+
+    // Values and lines, byte offsets move with the line endings.
+    fn invalid_lines(report: &Report) -> Vec<(u128, usize)> {
+        report.invalid_numbers.iter().map(|invalid| (invalid.value, invalid.line)).collect()
+    }
+
+    #[test]
+    fn parses_crlf_tokens() {
+        let format = NumberFormat::new::<u128>(&Config::default()).unwrap();
+        assert_eq!(parse_number_from_str_buffer::<u128>(b"15\r", format), Some(15));
+        assert_eq!(parse_numbers(b"15\r\n18\r\n\r\n14\r\n", &Config::default()).unwrap(), vec![15, 18, 14]);
+        assert_eq!(parse_numbers(b"15\r\n18", &Config::default()).unwrap(), vec![15, 18]);
+    }
+
+    // Without Config::crlf the chunks end on a '\r', which the scan drops like any trailing whitespace.
+    #[test]
+    fn crlf_input_matches_lf_input_across_chunk_boundaries() {
+        let lf = &include_bytes!("../resources/challenge_input.txt")[..3000];
+        let crlf = with_crlf(lf);
+        for parallelism in 1..=12 {
+            let config = Config::builder().parallelism(parallelism).window(5).build();
+            let (lf_report, crlf_report) = (analyze(lf, &config).unwrap(), analyze(&crlf, &config).unwrap());
+            assert_eq!(invalid_lines(&crlf_report), invalid_lines(&lf_report), "parallelism {}", parallelism);
+            assert_eq!((crlf_report.parsed, crlf_report.validated), (lf_report.parsed, lf_report.validated));
+        }
+    }
+
+    // With Config::crlf no chunk ends on the '\r', wherever the split points fall, and the results match the LF input.
+    #[test]
+    fn crlf_bounds_stop_before_the_carriage_return() {
+        let lf = [&include_bytes!("../resources/challenge_input.txt")[..2000], b"\n\n7\n".as_slice()].concat();
+        let crlf = with_crlf(&lf);
+        for parallelism in 1..=16 {
+            for records_per_chunk in [None, Some(1), Some(3), Some(50)] {
+                let config = Config::builder().parallelism(parallelism).records_per_chunk(records_per_chunk).window(5).build();
+                let lf_report = analyze(&lf, &config).unwrap();
+                let config = Config { crlf: true, verify_bounds: true, profile_chunks: true, ..config };
+                let crlf_report = analyze(&crlf, &config).unwrap();
+                assert!(crlf_report.chunk_profiles.iter().all(|profile| crlf[profile.byte_range.end - 1] != b'\r'), "{:?}", crlf_report.chunk_profiles);
+                assert_eq!(invalid_lines(&crlf_report), invalid_lines(&lf_report), "parallelism {}", parallelism);
+                assert_eq!((crlf_report.parsed, crlf_report.validated), (lf_report.parsed, lf_report.validated));
+            }
+        }
+    }
+
+    // Whether each search of the window finds a pair, the sorted one on a sorted copy.
+    fn unsigned_searches(target: u128, candidates: &[u128]) -> Vec<bool> {
+        let mut sorted = candidates.to_vec();
+        sorted.sort_unstable();
+        let found = vec![
+            is_number_valid_nested(target, candidates, true, &mut 0).is_some(),
+            is_number_valid_hashset(target, candidates, true, &mut 0).is_some(),
+            is_number_valid_sorted(target, &sorted, true, &mut 0).is_some(),
+        ];
+        #[cfg(parallel)]
+        let found = [found, vec![is_number_valid_parallel(target, candidates, true, &mut 0).is_some()]].concat();
+        found
+    }
+
+    fn signed_searches(target: i128, candidates: &[i128]) -> Vec<bool> {
+        let mut sorted = candidates.to_vec();
+        sorted.sort_unstable();
+        let found = vec![
+            is_signed_number_valid_nested(target, candidates, true, &mut 0).is_some(),
+            is_signed_number_valid_hashset(target, candidates, true, &mut 0).is_some(),
+            is_signed_number_valid_sorted(target, &sorted, true, &mut 0).is_some(),
+        ];
+        #[cfg(parallel)]
+        let found = [found, vec![is_signed_number_valid_parallel(target, candidates, true, &mut 0).is_some()]].concat();
+        found
+    }
+
+    // Sums past the end of the range are rejected rather than wrapped, and never panic in debug builds.
+    #[test]
+    fn overflowing_sums_never_validate() {
+        let max = u128::MAX;
+        for (target, candidates, valid) in [
+            (max, vec![max, max], false),
+            (max - 1, vec![max - 1, max, max], false),
+            (max, vec![max - 1, max, 1], true),
+            (max, vec![max / 2, max / 2 + 1], true),
+            (max, vec![max, 0], true),
+        ] {
+            assert!(unsigned_searches(target, &candidates).iter().all(|&found| found == valid), "{} {:?}", target, candidates);
+        }
+        for (target, candidates, valid) in [
+            // Both would wrap around to the target.
+            (i128::MAX, vec![i128::MIN, -1], false),
+            (i128::MIN, vec![i128::MAX, 1], false),
+            (i128::MIN, vec![i128::MIN, i128::MIN], false),
+            (i128::MAX, vec![i128::MAX, i128::MAX, i128::MAX - 1, 1], true),
+            (-1, vec![i128::MIN, i128::MAX], true),
+        ] {
+            assert!(signed_searches(target, &candidates).iter().all(|&found| found == valid), "{} {:?}", target, candidates);
+        }
+    }
+
+    #[test]
+    fn empty_input_is_an_error() {
+        for data in [&b""[..], b"\n", b"  \n\n\t\r\n "] {
+            assert!(matches!(analyze(data, &Config::default()), Err(ProcessError::EmptyInput)), "{:?}", data);
+        }
+    }
+
+    // None of the numbers has a full window before it, so none is validated, whatever the chunks.
+    #[test]
+    fn input_shorter_than_window_validates_nothing() {
+        let data = lines(&[1, 3, 9, 27, 81, 243, 729, 2187, 6561, 19683]);
+        for parallelism in 1..=8 {
+            let report = analyze(&data, &Config::builder().parallelism(parallelism).window(100).build()).unwrap();
+            assert!(report.invalid_numbers.is_empty(), "parallelism {}", parallelism);
+            assert_eq!((report.parsed, report.validated), (10, 0));
+        }
+        assert!(find_invalid_numbers_reference(&data, 100).unwrap().is_empty());
+    }
+
+    // Wherever the chunks split, the overlap scanned by two of them reports each invalid number once, and the same value
+    // found again later in the file is still reported on its own.
+    #[test]
+    fn invalid_number_in_chunk_overlap_is_reported_once() {
+        let data = lines(&[1, 2, 3, 5, 8, 100, 108, 208, 316, 524, 840, 1364, 100]);
+        let expected = vec![
+            InvalidNumber { value: 100, line: 6, byte_offset: 10 },
+            InvalidNumber { value: 100, line: 13, byte_offset: data.len() - 4 },
+        ];
+        for parallelism in 1..=12 {
+            for records_per_chunk in [None, Some(1), Some(2), Some(3), Some(4), Some(5)] {
+                let config = Config::builder().parallelism(parallelism).records_per_chunk(records_per_chunk).window(2).build();
+                assert_eq!(analyze(&data, &config).unwrap().invalid_numbers, expected, "parallelism {} records {:?}", parallelism, records_per_chunk);
+            }
+        }
+    }
+
+    // Two chunks reporting the number in their overlap, merged out of order, with the same value again further on.
+    #[test]
+    fn merge_collapses_numbers_by_position_not_value() {
+        let invalid = |value: u128, byte_offset: usize| InvalidNumber { value, line: byte_offset / 4 + 1, byte_offset };
+        let mut merged = vec![invalid(100, 40), invalid(7, 12), invalid(100, 20), invalid(100, 20), invalid(9, 4)];
+        into_file_order(&mut merged, |invalid| invalid.byte_offset);
+        assert_eq!(merged, vec![invalid(9, 4), invalid(7, 12), invalid(100, 20), invalid(100, 40)]);
+    }
+
+    // Blank lines hold no number, so only the lines and offsets move.
+    #[test]
+    fn blank_lines_leave_the_counts_unchanged() {
+        let plain = b"1\n2\n3\n5\n8\n100\n108\n";
+        let blank = b"\n1\n2\n\n3\n5\n   \n\n8\n100\n\t\n108\n\n\n";
+        for parallelism in 1..=6 {
+            let config = Config::builder().parallelism(parallelism).window(2).build();
+            let (plain_report, blank_report) = (analyze(plain, &config).unwrap(), analyze(blank, &config).unwrap());
+            assert_eq!((blank_report.parsed, blank_report.validated), (7, 5));
+            assert_eq!((blank_report.parsed, blank_report.validated), (plain_report.parsed, plain_report.validated));
+            assert_eq!(blank_report.invalid_numbers, vec![InvalidNumber { value: 100, line: 10, byte_offset: 17 }], "parallelism {}", parallelism);
+        }
+        assert_eq!(parse_numbers(blank, &Config::default()).unwrap(), vec![1, 2, 3, 5, 8, 100, 108]);
+    }
+
+    // The delimiter at position zero ends a blank first line, no bound can point before the data.
+    #[test]
+    fn buffer_starting_with_a_delimiter() {
+        assert_eq!(right_bounds(b"\n1\n2\n3\n", 0, 1), (1, 3));
+        assert_eq!(right_bounds(b"\n\n1\n2\n", 0, 3), (0, 4));
+        let crlf_bounds = get_right_bounds(b"\r\n1\r\n2\r\n", 0, 1, Separator::Crlf, |record| !is_skipped_record(record, None));
+        assert_eq!(crlf_bounds, (2, 5));
+        for parallelism in 1..=4 {
+            assert_eq!(compute_bounds(b"\n1\n2\n3\n", parallelism, 1).first().map(|bounds| bounds.0), Some(0));
+            let config = Config::builder().parallelism(parallelism).window(1).build();
+            assert_eq!(analyze(b"\n1\n2\n3\n", &config).unwrap().invalid_numbers, vec![
+                InvalidNumber { value: 2, line: 3, byte_offset: 3 },
+                InvalidNumber { value: 3, line: 4, byte_offset: 5 },
+            ]);
+        }
+    }
+
+    #[test]
+    fn fifty_digit_token_is_too_long() {
+        let data = format!("1\n2\n{}\n3\n", "7".repeat(50)).into_bytes();
+        for parallelism in 1..=4 {
+            let result = analyze(&data, &Config::builder().parallelism(parallelism).window(1).build());
+            assert!(matches!(result, Err(ProcessError::NumberTooLong { line: 3, .. })), "{:?}", result);
+        }
+        assert!(matches!(analyze_stream(&data[..], &Config::builder().window(1).build()), Err(ProcessError::NumberTooLong { line: 3, .. })));
+        assert!(matches!(parse_numbers(&data, &Config::default()), Err(ProcessError::NumberTooLong { line: 3, .. })));
+        let skipped = analyze(&data, &Config::builder().window(1).skip_invalid_tokens(true).build()).unwrap();
+        assert_eq!(skipped.parsed, 3);
+    }
+
+    // 5 after [0, 5] is only valid through the zero pair, 10 after [5, 5] stays valid either way.
+    #[test]
+    fn zero_pairs_validate_unless_rejected() {
+        let data = b"0\n5\n5\n10\n";
+        for (no_zero_pair, expected) in [(false, vec![]), (true, vec![InvalidNumber { value: 5, line: 3, byte_offset: 4 }])] {
+            for sorted_window in [false, true] {
+                let config = Config::builder().window(2).no_zero_pair(no_zero_pair).sorted_window(sorted_window).build();
+                assert_eq!(analyze(data, &config).unwrap().invalid_numbers, expected, "no_zero_pair {} sorted_window {}", no_zero_pair, sorted_window);
+                assert_eq!(analyze_reference(data, &config).unwrap().invalid_numbers, expected);
+                assert_eq!(analyze_stream(&data[..], &config).unwrap().invalid_numbers, expected);
+            }
+        }
+        assert_eq!(is_number_valid(5, &[0, 5]), Some((0, 5)));
+        assert_eq!(is_number_valid_nested(5, &[0, 5], false, &mut 0), None);
+        assert_eq!(is_number_valid_hashset(5, &[0, 5], false, &mut 0), None);
+        assert_eq!(is_number_valid_sorted(5, &[0, 5], false, &mut 0), None);
+        assert_eq!(is_signed_number_valid_hashset(5, &[0, 5], false, &mut 0), None);
+        assert_eq!(is_signed_number_valid_nested(5, &[5, 0], false, &mut 0), None);
+        assert_eq!(is_signed_number_valid_sorted(5, &[0, 5], false, &mut 0), None);
+    }
+
+    // The last number is validated once whether or not a delimiter follows it, valid or not, whatever the chunks.
+    #[test]
+    fn last_number_is_evaluated_once() {
+        for last in ["13", "100"] {
+            for ending in ["", "\n"] {
+                let data = format!("1\n2\n3\n5\n8\n{}{}", last, ending).into_bytes();
+                for parallelism in 1..=6 {
+                    let config = Config::builder().parallelism(parallelism).window(2).keep_valid(true).explain(true).build();
+                    let report = analyze(&data, &config).unwrap();
+                    assert_eq!((report.parsed, report.validated), (6, 4), "{:?} parallelism {}", data, parallelism);
+                    let evaluated = report.explanations.iter().filter(|explanation| explanation.byte_offset == 10).count();
+                    let listed = report.invalid_numbers.iter().chain(&report.valid_numbers).filter(|number| number.byte_offset == 10).count();
+                    assert_eq!((evaluated, listed), (1, 1), "{:?} parallelism {}", data, parallelism);
+                    assert_eq!(report.invalid_numbers.is_empty(), last == "13");
+                }
+            }
+        }
+    }
+
+    proptest! {
+        // Every byte of every record is in a chunk, and each chunk reaches window records into the next one, fewer only
+        // at the end of the data.
+        #[test]
+        fn compute_bounds_cover_records_and_overlap_by_window(
+            records in prop::collection::vec(prop_oneof![4 => (0..1000u32).prop_map(|number| number.to_string()), 1 => Just(String::new())], 1..120)
+                .prop_filter("holds a number", |records| records.iter().any(|record| !record.is_empty())),
+            trailing_newline in any::<bool>(),
+            parallelism in 1..20usize,
+            window in 1..8usize,
+        ) {
+            let mut data = records.join("\n").into_bytes();
+            if trailing_newline {
+                data.push(b'\n');
+            }
+            let bounds = compute_bounds(&data, parallelism, window);
+            prop_assert!(!bounds.is_empty() && bounds.len() <= parallelism);
+            let data_end = if data.ends_with(b"\n") { data.len() - 2 } else { data.len() - 1 };
+            prop_assert_eq!(bounds[0].0, 0);
+            prop_assert_eq!(bounds[bounds.len() - 1].1, data_end);
+            for (idx, &byte) in data.iter().enumerate() {
+                prop_assert!(byte == b'\n' || bounds.iter().any(|&(left, right)| (left..=right).contains(&idx)), "byte {} of {:?} in no chunk of {:?}", idx, data, bounds);
+            }
+            for pair in bounds.windows(2) {
+                let ((_, right), (next_left, _)) = (pair[0], pair[1]);
+                let overlap = data[next_left..=right].split(|&byte| byte == b'\n').filter(|record| !record.is_empty()).count();
+                prop_assert!(overlap == window || (overlap < window && right == data_end), "overlap of {} records in {:?} for {:?}", overlap, bounds, data);
+            }
+        }
+    }
+
+    // A candidate never pairs with itself, in small windows and in those large enough for the hashset.
+    #[test]
+    fn single_candidate_never_validates_alone() {
+        for filler in [0, HASHSET_MIN_ITEM_RANGE_SIZE] {
+            let window = |candidates: &[u128]| [vec![1000; filler], candidates.to_vec()].concat();
+            assert_eq!(is_number_valid(10, &window(&[10])), None);
+            assert_eq!(is_number_valid(10, &window(&[5])), None);
+            assert_eq!(is_number_valid(10, &window(&[5, 5])), Some((5, 5)));
+            assert_eq!(is_number_valid_in_sorted(10, &[10]), None);
+            assert_eq!(is_number_valid_in_sorted(10, &[5]), None);
+            assert_eq!(is_number_valid_in_sorted(10, &[5, 5]), Some((5, 5)));
+            let signed_window = |candidates: &[i128]| [vec![1000; filler], candidates.to_vec()].concat();
+            assert_eq!(is_signed_number_valid(10, &signed_window(&[10])), None);
+            assert_eq!(is_signed_number_valid(-10, &signed_window(&[-5])), None);
+            assert_eq!(is_signed_number_valid(-10, &signed_window(&[-5, -5])), Some((-5, -5)));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn round_trip<T: Serialize + serde::de::DeserializeOwned + PartialEq + fmt::Debug>(value: T) {
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(serde_json::from_str::<T>(&json).unwrap(), value, "{}", json);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn result_types_round_trip_through_json() {
+        round_trip(InvalidNumber { value: u128::MAX, line: 3, byte_offset: 17 });
+        round_trip(InvalidNumber { value: i128::MIN, line: 1, byte_offset: 0 });
+        round_trip(InvalidNumber { value: Float(-2.5), line: 2, byte_offset: 4 });
+        round_trip(Explanation { value: 10u128, line: 4, byte_offset: 6, addends: Some((3, 7)) });
+        round_trip(Explanation { value: 10u128, line: 4, byte_offset: 6, addends: None });
+        round_trip(DuplicateWindow { value: 8u128, line: 5, byte_offset: 9, repeated: 2 });
+        round_trip(WindowResult { window: 25, invalid_numbers: vec![InvalidNumber { value: 14u128, line: 315, byte_offset: 878 }], validated: 10075 });
+        let report = analyze(include_bytes!("../resources/challenge_input.txt"), &Config::default()).unwrap();
+        round_trip(report.invalid_numbers);
+    }
+
+    // The reverse scan meets the last malformed token of a chunk first, yet the first one of the input is reported
+    // however it is split.
+    #[test]
+    fn first_malformed_token_is_reported_at_any_parallelism() {
+        let mut numbers: Vec<String> = (1..=6000).map(|number| number.to_string()).collect();
+        numbers[0] = "x1".to_string();
+        numbers[5001] = "x5002".to_string();
+        let data = lines(&numbers);
+        for parallelism in [1, 2, 4, 8, 16] {
+            let config = Config::builder().parallelism(parallelism).window(3).build();
+            let err = find_invalid_numbers_with_config(&data, &config).unwrap_err();
+            assert!(matches!(&err, ProcessError::ParseNumber { bytes, line: 1 } if bytes == b"x1"), "{parallelism}: {err}");
+        }
+        // Tokens sharing a line are told apart by their offset.
+        let data = numbers.join(",").into_bytes();
+        for parallelism in [1, 2, 4, 8, 16] {
+            let config = Config::builder().parallelism(parallelism).window(3).delimiter(b',').build();
+            let err = find_invalid_numbers_with_config(&data, &config).unwrap_err();
+            assert!(matches!(&err, ProcessError::ParseNumber { bytes, line: 1 } if bytes == b"x1"), "{parallelism}: {err}");
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn first_malformed_token_matches_forward_scan(
+            numbers in prop::collection::vec(0..40u128, 1..200),
+            bad_lines in prop::collection::vec(any::<prop::sample::Index>(), 1..4),
+            parallelism in 1..12usize,
+            item_range_size in 1..40usize,
+        ) {
+            let mut tokens: Vec<String> = numbers.iter().map(u128::to_string).collect();
+            for bad_line in &bad_lines {
+                let idx = bad_line.index(tokens.len());
+                tokens[idx] = format!("x{idx}");
+            }
+            let first_bad = tokens.iter().position(|token| token.starts_with('x')).unwrap();
+            let config = Config::builder().parallelism(parallelism).window(item_range_size).build();
+            let err = find_invalid_numbers_with_config(&lines(&tokens), &config).unwrap_err();
+            prop_assert!(matches!(&err, ProcessError::ParseNumber { line, .. } if *line == first_bad + 1), "{}", err);
+        }
+    }
+
+    #[test]
+    fn pair_lookup_picks_the_search() {
+        let search = |lookup| PairSearch { lookup, ..PairSearch::sequential() };
+        assert!(!search(PairLookup::Auto).uses_hashset(HASHSET_MIN_ITEM_RANGE_SIZE - 1));
+        assert!(search(PairLookup::Auto).uses_hashset(HASHSET_MIN_ITEM_RANGE_SIZE));
+        assert!(!search(PairLookup::Nested).uses_hashset(HASHSET_MIN_ITEM_RANGE_SIZE * 8));
+        assert!(search(PairLookup::Hashset).uses_hashset(2));
+        // Either lookup finds the same pair on both sides of the threshold.
+        for filler in [0, HASHSET_MIN_ITEM_RANGE_SIZE] {
+            let candidates = [vec![1000; filler], vec![3, 9, 7]].concat();
+            for pair_lookup in [PairLookup::Auto, PairLookup::Nested, PairLookup::Hashset] {
+                let config = Config::builder().pair_lookup(pair_lookup).build();
+                assert_eq!(is_number_valid_with(10, &candidates, &config).map(|(first, second)| first.min(second)), Some(3));
+                assert_eq!(is_number_valid_with(11, &candidates, &config), None);
+            }
+        }
+    }
+}
@@ -1,161 +1,1726 @@
+use std::borrow::Cow;
 use std::cmp::max;
+use std::collections::HashSet;
 use std::env;
+use std::ffi::OsString;
 use std::fs::File;
+use std::fmt;
+use std::hash::Hash;
+use std::io::{self, BufReader, BufWriter, IsTerminal, Read, Write};
+use std::ops::{Deref, Range};
+use std::path::{Path, PathBuf};
+use std::process;
 use std::str::FromStr;
-use std::thread::available_parallelism;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, available_parallelism};
+use std::time::{Duration, Instant};
 
-use anyhow::Context;
+use anyhow::{bail, Context};
+use flate2::read::GzDecoder;
+use gdlauncher_test::{analyze, analyze_float, analyze_reference, analyze_reference_float, analyze_reference_signed, analyze_signed, analyze_stream, analyze_stream_float, analyze_stream_signed, find_contiguous_range_sum, find_invalid_numbers_with, parse_numbers, ChunkProfile, Config, Explanation, Float, InvalidNumber, ProcessError, Report, ValidNumber, DuplicateWindow, WindowResult, DEFAULT_DELIMITER, DEFAULT_EPSILON, DEFAULT_ITEM_RANGE_SIZE, DEFAULT_RADIX, MIN_PARALLELISM};
+#[cfg(feature = "bigint")]
+use gdlauncher_test::{analyze_bigint, analyze_reference_bigint, analyze_stream_bigint, BigUint};
+#[cfg(feature = "mmap")]
 use memmap::{Mmap, MmapOptions};
-use rayon::iter::IntoParallelRefIterator;
-use rayon::iter::ParallelIterator;
+use serde::Serialize;
+use tracing::{debug, info, warn};
+use tracing_subscriber::EnvFilter;
 
-const MIN_PARALLELISM: usize = 4;
-const ITEM_RANGE_SIZE: usize = 100;
-const RELATIVE_FILE_PATH: &str = "/resources/challenge_input.txt";
-const NUMBERS_BUFFER_SIZE: usize = ITEM_RANGE_SIZE + 1;
-const MARGIN_AVOID_LINE_BREAK: usize = 2;
-const SPLIT_MARKER: u8 = b'\n';
-const STR_U128_LEN: usize = 39;
+const ITEM_RANGE_SIZE_ENV: &str = "WINDOW_SIZE";
+const DEFAULT_LOG_LEVEL: &str = "info";
+// Input bytes per chunk below which auto-detected threads are scaled down.
+const AUTO_BYTES_PER_THREAD: usize = 1 << 20;
+// Wait before the first retry of --retry-open, doubled on each further one up to RETRY_OPEN_MAX_DELAY.
+const RETRY_OPEN_DELAY: Duration = Duration::from_millis(100);
+const RETRY_OPEN_MAX_DELAY: Duration = Duration::from_secs(2);
+// Exit code of a run stopped by Ctrl-C with --interruptible, as shells report a SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+// Default input relative to the current directory, joined segment by segment to use the platform separator.
+const RELATIVE_FILE_PATH: [&str; 2] = ["resources", "challenge_input.txt"];
+const STDIN_ARG: &str = "-";
+const GZIP_EXTENSION: &str = "gz";
+const ZSTD_EXTENSION: &str = "zst";
+const BZIP2_EXTENSION: &str = "bz2";
+const SKIP_INVALID_TOKENS_FLAG: &str = "--skip-invalid-tokens";
+const PART2_FLAG: &str = "--part2";
+const FORMAT_FLAG: &str = "--format";
+const THREADS_FLAG: &str = "--threads";
+const PROGRESS_FLAG: &str = "--progress";
+const DELIMITER_FLAG: &str = "--delimiter";
+const WHITESPACE_FLAG: &str = "--whitespace";
+const OUTPUT_FLAG: &str = "--output";
+const SIGNED_FLAG: &str = "--signed";
+const EXPLAIN_FLAG: &str = "--explain";
+const TIME_FLAG: &str = "--time";
+const RADIX_FLAG: &str = "--radix";
+const STREAM_FLAG: &str = "--stream";
+const FIRST_ONLY_FLAG: &str = "--first-only";
+const ALLOW_UNDERSCORES_FLAG: &str = "--allow-underscores";
+const SKIP_HEADER_FLAG: &str = "--skip-header";
+const NO_ZERO_PAIR_FLAG: &str = "--no-zero-pair";
+const COUNT_ONLY_FLAG: &str = "--count-only";
+const PARALLEL_PAIRS_FLAG: &str = "--parallel-pairs";
+const SORTED_WINDOW_FLAG: &str = "--sorted-window";
+const VERIFY_FLAG: &str = "--verify";
+const MAX_MEMORY_FLAG: &str = "--max-memory";
+const BIGINT_FLAG: &str = "--bigint";
+const UNIQUE_FLAG: &str = "--unique";
+const SORT_FLAG: &str = "--sort";
+const STATS_FLAG: &str = "--stats";
+const OFFSET_FLAG: &str = "--offset";
+const LENGTH_FLAG: &str = "--length";
+const TIME_BREAKDOWN_FLAG: &str = "--time-breakdown";
+const WINDOW_FROM_HEADER_FLAG: &str = "--window-from-header";
+const FLOAT_FLAG: &str = "--float";
+const EPSILON_FLAG: &str = "--epsilon";
+const MAX_INVALID_FLAG: &str = "--max-invalid";
+const COMMENT_FLAG: &str = "--comment";
+const DETERMINISTIC_FLAG: &str = "--deterministic";
+const RETRY_OPEN_FLAG: &str = "--retry-open";
+const DEBUG_COUNTERS_FLAG: &str = "--debug-counters";
+const EMIT_FLAG: &str = "--emit";
+const REPORT_DUPES_FLAG: &str = "--report-dupes";
+const INTERRUPTIBLE_FLAG: &str = "--interruptible";
+const RECORDS_PER_CHUNK_FLAG: &str = "--records-per-chunk";
+const EXPECTED_INVALID_FLAG: &str = "--expected-invalid";
+const VERIFY_BOUNDS_FLAG: &str = "--verify-bounds";
+const WINDOW_FLAG: &str = "--window";
+const PROFILE_CHUNKS_FLAG: &str = "--profile-chunks";
+
+struct Args {
+    file_paths: Vec<OsString>,
+    skip_invalid_tokens: bool,
+    part2: bool,
+    format: OutputFormat,
+    // 0 means auto-detect.
+    threads: usize,
+    progress: bool,
+    delimiter: u8,
+    // Set by a "\r\n" delimiter, which keeps '\n' as the delimiter byte.
+    crlf: bool,
+    whitespace: bool,
+    output: Option<PathBuf>,
+    signed: bool,
+    explain: bool,
+    time: TimeUnit,
+    radix: u32,
+    stream: bool,
+    first_only: bool,
+    allow_underscores: bool,
+    skip_header: usize,
+    no_zero_pair: bool,
+    count_only: bool,
+    parallel_pairs: bool,
+    sorted_window: bool,
+    verify: bool,
+    max_memory: Option<usize>,
+    bigint: bool,
+    unique: bool,
+    sort: bool,
+    stats: bool,
+    offset: Option<usize>,
+    length: Option<usize>,
+    time_breakdown: bool,
+    window_from_header: bool,
+    float: bool,
+    epsilon: Option<f64>,
+    max_invalid: Option<usize>,
+    comment: Option<u8>,
+    deterministic: bool,
+    retry_open: u32,
+    debug_counters: bool,
+    emit: Emit,
+    report_dupes: bool,
+    interruptible: bool,
+    records_per_chunk: Option<usize>,
+    expected_invalid: Option<usize>,
+    verify_bounds: bool,
+    // Ascending and distinct, empty without --window.
+    windows: Vec<usize>,
+    profile_chunks: bool,
+}
+
+// What parse_args starts from, each flag then changes its own field.
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            file_paths: Vec::new(),
+            skip_invalid_tokens: false,
+            part2: false,
+            format: OutputFormat::Text,
+            threads: 0,
+            progress: false,
+            delimiter: DEFAULT_DELIMITER,
+            crlf: false,
+            whitespace: false,
+            output: None,
+            signed: false,
+            explain: false,
+            time: TimeUnit::Millis,
+            radix: DEFAULT_RADIX,
+            stream: false,
+            first_only: false,
+            allow_underscores: false,
+            skip_header: 0,
+            no_zero_pair: false,
+            count_only: false,
+            parallel_pairs: false,
+            sorted_window: false,
+            verify: false,
+            max_memory: None,
+            bigint: false,
+            unique: false,
+            sort: false,
+            stats: false,
+            offset: None,
+            length: None,
+            time_breakdown: false,
+            window_from_header: false,
+            float: false,
+            epsilon: None,
+            max_invalid: None,
+            comment: None,
+            deterministic: false,
+            retry_open: 0,
+            debug_counters: false,
+            emit: Emit::Invalid,
+            report_dupes: false,
+            interruptible: false,
+            records_per_chunk: None,
+            expected_invalid: None,
+            verify_bounds: false,
+            profile_chunks: false,
+            windows: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
+    // One JSON object per listed number and line, without any summary. Written as the numbers are found when
+    // streams_ndjson allows it, otherwise once the report is complete.
+    Ndjson,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            _ => bail!("Unknown format '{}', expected text, json or ndjson", value),
+        }
+    }
+}
+
+// Numbers listed in the output. Valid ones are only kept when listed, as they are usually most of the input.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Emit {
+    Invalid,
+    Valid,
+    Both,
+}
+
+impl FromStr for Emit {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "invalid" => Ok(Emit::Invalid),
+            "valid" => Ok(Emit::Valid),
+            "both" => Ok(Emit::Both),
+            _ => bail!("Unknown {} value '{}', expected invalid, valid or both", EMIT_FLAG, value),
+        }
+    }
+}
+
+// Unit of the elapsed time line in text output, none hides it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TimeUnit {
+    None,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl TimeUnit {
+    // None still formats in milliseconds, for the lines printed regardless of --time.
+    fn format(self, duration: Duration) -> String {
+        match self {
+            TimeUnit::None | TimeUnit::Millis => format!("{} milliseconds", duration.as_millis()),
+            TimeUnit::Micros => format!("{} microseconds", duration.as_micros()),
+            TimeUnit::Nanos => format!("{} nanoseconds", duration.as_nanos()),
+        }
+    }
+}
+
+impl FromStr for TimeUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "none" => Ok(TimeUnit::None),
+            "ms" => Ok(TimeUnit::Millis),
+            "us" => Ok(TimeUnit::Micros),
+            "ns" => Ok(TimeUnit::Nanos),
+            _ => bail!("Unknown time unit '{}', expected none, ms, us or ns", value),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonFirstOutput<'a, T> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<&'a str>,
+    first_invalid: Option<&'a T>,
+}
+
+#[derive(Serialize)]
+struct NdjsonLine<'a, N> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<&'a str>,
+    // Only set with --emit both.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    valid: Option<bool>,
+    #[serde(flatten)]
+    number: &'a N,
+}
+
+#[derive(Serialize)]
+struct JsonOutput<'a, T> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<&'a str>,
+    count: usize,
+    parsed: usize,
+    validated: usize,
+    valid: usize,
+    first_invalid: Option<&'a T>,
+    // Count of invalid_numbers with --unique, count still includes the repeated values.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distinct: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<&'a Stats<T>>,
+    elapsed_micros: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_breakdown: Option<JsonTimeBreakdown>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comparisons: Option<u64>,
+    // Left out with --emit valid.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    invalid_numbers: Option<&'a [InvalidNumber<T>]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    valid_numbers: Option<&'a [ValidNumber<T>]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explanations: Option<&'a [Explanation<T>]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicate_windows: Option<&'a [DuplicateWindow<T>]>,
+    // Only present, and true, once Ctrl-C stopped the run with --interruptible.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interrupted: Option<bool>,
+    // The windows of --window below the largest one, which the rest of the output is for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    windows: Option<&'a [WindowResult<T>]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chunks: Option<Vec<JsonChunkProfile>>,
+}
+
+// Summary of the listed invalid numbers printed with --stats, the mean of integers is rounded down.
+#[derive(Serialize)]
+struct Stats<T> {
+    count: usize,
+    min: T,
+    max: T,
+    mean: T,
+}
+
+#[derive(Serialize)]
+struct JsonTimeBreakdown {
+    load_micros: u128,
+    bounds_micros: u128,
+    compute_micros: u128,
+}
+
+// A chunk of --profile-chunks, end is exclusive.
+#[derive(Serialize)]
+struct JsonChunkProfile {
+    start: usize,
+    end: usize,
+    records: usize,
+    elapsed_micros: u128,
+}
+
+// Elapsed time of one input together with its size in bytes, after decompression.
+// Load covers opening and mapping the input, or reading it into memory, and is zero when streaming.
+#[derive(Clone, Copy)]
+struct Timing {
+    elapsed: Duration,
+    load: Duration,
+    bytes: usize,
+}
+
+impl Timing {
+    // Decimal megabytes per second, an input processed too fast to measure has no meaningful rate.
+    fn throughput(self) -> String {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return "n/a MB/s".to_string();
+        }
+        format!("{:.2} MB/s", self.bytes as f64 / 1e6 / seconds)
+    }
+}
+
+// Counts the bytes handed to the stream analysis so its throughput can be reported.
+struct CountingReader<R> {
+    inner: R,
+    bytes: usize,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.bytes += read;
+        Ok(read)
+    }
+}
+
+// Input bytes either mapped from a file or read into memory when mapping is not possible (e.g. pipes).
+enum Input {
+    #[cfg(feature = "mmap")]
+    Mmap(Mmap),
+    Buffer(Vec<u8>),
+}
+
+impl From<Vec<u8>> for Input {
+    fn from(buffer: Vec<u8>) -> Self {
+        Input::Buffer(buffer)
+    }
+}
+
+impl Deref for Input {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            #[cfg(feature = "mmap")]
+            Input::Mmap(mmap) => mmap,
+            Input::Buffer(buffer) => buffer,
+        }
+    }
+}
 
 // This solution identifies and calculates all invalid numbers in the given input file and not only the first one.
 fn main() -> anyhow::Result<()> {
-    let start = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards").as_micros();
+    init_logging();
+    let args = parse_args()?;
+    let parallelism = resolve_parallelism(args.threads)?;
+    // The largest window sets the overlap of the chunks, the smaller ones are checked in the same scan.
+    let (item_range_size, extra_windows) = match args.windows.split_last() {
+        Some((&largest, smaller)) => (largest, smaller.to_vec()),
+        None => (resolve_item_range_size()?, Vec::new()),
+    };
+    let interrupt = if args.interruptible { Some(install_interrupt_handler()?) } else { None };
 
-    let parallelism = max(MIN_PARALLELISM, available_parallelism()?.get());
-    let current_dir = env::current_dir()?;
-    let current_dir_str = current_dir.to_str().context("Path to str conversion failed")?;
-    let file_path = format!("{}{}", current_dir_str, RELATIVE_FILE_PATH);
+    let config = build_config(&args, parallelism, item_range_size, extra_windows, interrupt);
 
-    let file = File::open(file_path)?;
-    let mmap = unsafe { MmapOptions::new().map(&file) }?;
+    let mut output = match &args.output {
+        Some(path) => Some(BufWriter::new(File::create(path).with_context(|| format!("Failed to create {}", path.display()))?)),
+        None => None,
+    };
 
-    if mmap[mmap.len() - 1] != b'\n' {
-        panic!("File must end with EOF marker");
+    if args.file_paths.len() <= 1 {
+        run(args.file_paths.first().cloned(), None, &args, &config, output.as_mut())?;
+        flush_output(output)?;
+        exit_if_interrupted(&config);
+        return Ok(());
     }
 
-    let result: Vec<u128> = get_bounds(&mmap, parallelism)
-        .par_iter()
-        .flat_map(|(left, right)| process(&mmap, *left, *right))
-        .collect();
+    // Each file is processed on its own, failures are collected so the remaining files still run.
+    let mut total = 0;
+    let mut failures = Vec::new();
+    for file_path in &args.file_paths {
+        let name = file_path.to_string_lossy();
+        if args.format == OutputFormat::Text {
+            print_summary(&args, format_args!("== {} ==", name));
+        }
+        match run(Some(file_path.clone()), Some(&name), &args, &config, output.as_mut()) {
+            Ok(count) => total += count,
+            Err(err) => failures.push((name, err)),
+        }
+        // The remaining files would only be interrupted as soon as they start.
+        if is_interrupted(&config) {
+            break;
+        }
+    }
 
-    println!("{} microseconds", SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards").as_micros() - start);
+    flush_output(output)?;
+    exit_if_interrupted(&config);
+    if args.format == OutputFormat::Text {
+        print_summary(&args, format_args!("{} invalid numbers found in total across {} files.", total, args.file_paths.len() - failures.len()));
+    }
 
-    println!("{} invalid numbers found.\n{:?}", result.len(), result);
+    for (name, err) in &failures {
+        eprintln!("{}: {:#}", name, err);
+    }
+    if !failures.is_empty() {
+        bail!("{} of {} files failed", failures.len(), args.file_paths.len());
+    }
     Ok(())
 }
 
 
-fn get_bounds(mmap: &Mmap, parallelism: usize) -> Vec<(usize, usize)> {
-    let bytes_per_chunk = mmap.len() / parallelism;
-    let mut bounds: Vec<(usize, usize)> = Vec::with_capacity(parallelism);
+// The first Ctrl-C sets the flag the scans check, see Config::interrupt. A second one exits at once, e.g. while the
+// input is still loading or --verify runs the reference, which never check it.
+#[cfg(not(target_arch = "wasm32"))]
+fn install_interrupt_handler() -> anyhow::Result<Arc<AtomicBool>> {
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let handler_interrupt = Arc::clone(&interrupt);
+    ctrlc::set_handler(move || {
+        if handler_interrupt.swap(true, Ordering::Relaxed) {
+            process::exit(INTERRUPTED_EXIT_CODE);
+        }
+    })
+    .context("Failed to install the Ctrl-C handler")?;
+    Ok(interrupt)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn install_interrupt_handler() -> anyhow::Result<Arc<AtomicBool>> {
+    bail!("{} isn't supported on wasm32, which has no signals", INTERRUPTIBLE_FLAG)
+}
+
+fn is_interrupted(config: &Config) -> bool {
+    config.interrupt.as_ref().is_some_and(|interrupt| interrupt.load(Ordering::Relaxed))
+}
+
+// Only called once the partial results were printed and flushed, the exit code still tells them apart.
+fn exit_if_interrupted(config: &Config) {
+    if is_interrupted(config) {
+        process::exit(INTERRUPTED_EXIT_CODE);
+    }
+}
+
+// Without --offset the range starts at the beginning of the input, without --length it runs to its end.
+fn byte_range(args: &Args) -> Option<Range<usize>> {
+    if args.offset.is_none() && args.length.is_none() {
+        return None;
+    }
+    let offset = args.offset.unwrap_or(0);
+    Some(offset..args.length.map_or(usize::MAX, |length| offset.saturating_add(length)))
+}
+
+// With auto-detected threads small inputs get one chunk per AUTO_BYTES_PER_THREAD, so tiny chunks don't spend most of
+// their work on the overlap with the next one. Only the bytes of --offset and --length are split. --records-per-chunk
+// sizes the chunks itself.
+fn size_parallelism<'a>(config: &'a Config, len: usize, args: &Args) -> Cow<'a, Config> {
+    if args.threads != 0 || args.records_per_chunk.is_some() {
+        return Cow::Borrowed(config);
+    }
+    let len = config.byte_range.as_ref().map_or(len, |range| range.len().min(len));
+    let parallelism = (len / AUTO_BYTES_PER_THREAD).clamp(1, config.parallelism);
+    debug!(parallelism, bytes = len, "Sized the chunks to the input");
+    Cow::Owned(Config { parallelism, ..config.clone() })
+}
+
+// The library settings for the parsed flags, with the values main resolves from the environment and the window flags.
+fn build_config(args: &Args, parallelism: usize, item_range_size: usize, extra_windows: Vec<usize>, interrupt: Option<Arc<AtomicBool>>) -> Config {
+    Config::builder()
+        .parallelism(parallelism)
+        .window(item_range_size)
+        .skip_invalid_tokens(args.skip_invalid_tokens)
+        .progress(args.progress)
+        .delimiter(args.delimiter)
+        .crlf(args.crlf)
+        .whitespace_delimited(args.whitespace)
+        .explain(args.explain)
+        .radix(args.radix)
+        .allow_underscores(args.allow_underscores)
+        .first_only(args.first_only)
+        .skip_header(args.skip_header)
+        .no_zero_pair(args.no_zero_pair)
+        .count_only(args.count_only)
+        .parallel_pair_search(args.parallel_pairs)
+        .sorted_window(args.sorted_window)
+        .max_memory(args.max_memory)
+        .byte_range(byte_range(args))
+        .time_phases(args.time_breakdown)
+        .window_from_header(args.window_from_header)
+        .epsilon(args.epsilon.unwrap_or(DEFAULT_EPSILON))
+        .max_invalid(args.max_invalid)
+        .comment_prefix(args.comment)
+        .count_comparisons(args.debug_counters)
+        .keep_valid(args.emit != Emit::Invalid)
+        .report_dupes(args.report_dupes)
+        .interrupt(interrupt)
+        .records_per_chunk(args.records_per_chunk)
+        .expected_invalid(args.expected_invalid)
+        .verify_bounds(args.verify_bounds)
+        .profile_chunks(args.profile_chunks)
+        .extra_windows(extra_windows)
+        .build()
+}
+
+// Processes a single input and prints its result, returning the count of invalid numbers.
+// The file name is only included in the JSON output when several files are processed.
+fn run(file_path: Option<OsString>, file_name: Option<&str>, args: &Args, config: &Config, output: Option<&mut BufWriter<File>>) -> anyhow::Result<usize> {
+    #[cfg(feature = "bigint")]
+    if args.bigint {
+        return run_as::<BigUint>(file_path, file_name, args, config, output);
+    }
+    if args.float {
+        return run_as::<Float>(file_path, file_name, args, config, output);
+    }
+    if args.signed {
+        return run_as::<i128>(file_path, file_name, args, config, output);
+    }
+    if streams_ndjson(args) {
+        return run_ndjson(file_path, file_name, args, config, output);
+    }
+    run_as::<u128>(file_path, file_name, args, config, output)
+}
+
+// Only unsigned numbers have a callback scan, and it can't sort, dedup, verify or list anything but the invalid numbers
+// in file order. Nor does it read the byte range, stop on an interrupt or report progress.
+fn streams_ndjson(args: &Args) -> bool {
+    args.format == OutputFormat::Ndjson && args.emit == Emit::Invalid
+        && !(args.explain || args.sort || args.unique || args.verify || args.part2 || args.stream || args.interruptible || args.progress)
+        && args.offset.is_none() && args.length.is_none() && args.max_memory.is_none()
+}
+
+// Each invalid number is written as soon as the forward scan finds it, on the calling thread, instead of once every
+// chunk was merged into the report.
+fn run_ndjson(file_path: Option<OsString>, file_name: Option<&str>, args: &Args, config: &Config, output: Option<&mut BufWriter<File>>) -> anyhow::Result<usize> {
+    let input = load_input(file_path, args.max_memory, args.retry_open)?;
+    let mut writer = ndjson_writer(output);
+    let mut found = 0;
+    // The scan can't be stopped from the callback, so the first write error is returned once it ends.
+    let mut written = Ok(());
+    let scanned = find_invalid_numbers_with(&input, config, |invalid| {
+        found += 1;
+        if written.is_ok() {
+            written = write_ndjson_line(&mut writer, file_name, None, &invalid);
+        }
+    });
+    match scanned {
+        Err(ProcessError::EmptyInput) => {}
+        scanned => scanned?,
+    }
+    written?;
+    Ok(found)
+}
+
+fn run_as<T: CliNumber>(file_path: Option<OsString>, file_name: Option<&str>, args: &Args, config: &Config, output: Option<&mut BufWriter<File>>) -> anyhow::Result<usize> {
+    let start = Instant::now();
+    if args.stream {
+        let mut reader = CountingReader { inner: open_stream(file_path, args.retry_open)?, bytes: 0 };
+        let report = keep_unique(allow_empty(T::analyze_stream(&mut reader, config))?, args);
+        let stats = stats(&report, args);
+        print_report(&T::printable(&sort_by_value(&report, args)), stats.as_ref(), Timing { elapsed: start.elapsed(), load: Duration::ZERO, bytes: reader.bytes }, file_name, args, output)?;
+        return Ok(report.invalid_count);
+    }
+
+    let input = load_input(file_path, args.max_memory, args.retry_open)?;
+    run_on::<T>(&input, start, file_name, args, config, output)
+}
+
+// Everything after loading, so any bytes in memory can go through the same steps as an input file.
+// Load is measured from start up to the call.
+fn run_on<T: CliNumber>(data: &[u8], start: Instant, file_name: Option<&str>, args: &Args, config: &Config, output: Option<&mut BufWriter<File>>) -> anyhow::Result<usize> {
+    let load = start.elapsed();
+    let config = &size_parallelism(config, data.len(), args);
+    let report = keep_unique(allow_empty(T::analyze(data, config))?, args);
+    let stats = stats(&report, args);
+    print_report(&T::printable(&sort_by_value(&report, args)), stats.as_ref(), Timing { elapsed: start.elapsed(), load, bytes: data.len() }, file_name, args, output)?;
+    // Neither applies to the part of the input an interrupted run covered.
+    if report.interrupted {
+        return Ok(report.invalid_count);
+    }
+    if args.verify {
+        verify(&report, &keep_unique(allow_empty(T::analyze_reference(data, config))?, args), args)?;
+    }
+    if args.part2 {
+        T::run_part2(data, &report.invalid_numbers, config)?;
+    }
+    Ok(report.invalid_count)
+}
+
+// Entry points of the library for each numeric type the binary can analyze, picked once in run.
+trait CliNumber: fmt::Display + Ord + Hash + Clone + Default {
+    // What the reports are printed as.
+    type Printable: fmt::Display + Serialize + Clone;
+
+    fn analyze(data: &[u8], config: &Config) -> Result<Report<Self>, ProcessError>;
+    fn analyze_stream(reader: impl Read, config: &Config) -> Result<Report<Self>, ProcessError>;
+    fn analyze_reference(data: &[u8], config: &Config) -> Result<Report<Self>, ProcessError>;
+    fn printable(report: &Report<Self>) -> Cow<'_, Report<Self::Printable>>;
+    fn to_printable(&self) -> Self::Printable;
+    // Rounded down for integers, values is never empty.
+    fn mean(values: &[Self]) -> Self;
+
+    // Part two only applies to u128 numbers, parse_args rejects it for the other types.
+    fn run_part2(_data: &[u8], _result: &[InvalidNumber<Self>], _config: &Config) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl CliNumber for u128 {
+    type Printable = u128;
+
+    fn analyze(data: &[u8], config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze(data, config)
+    }
+
+    fn analyze_stream(reader: impl Read, config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze_stream(reader, config)
+    }
+
+    fn analyze_reference(data: &[u8], config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze_reference(data, config)
+    }
+
+    fn printable(report: &Report<Self>) -> Cow<'_, Report<Self>> {
+        Cow::Borrowed(report)
+    }
+
+    fn to_printable(&self) -> Self {
+        *self
+    }
+
+    // The sum of the values can overflow, so the quotients and remainders by the count are summed separately instead.
+    // Each remainder is below the count, their sum can't overflow for any count that fits in memory.
+    fn mean(values: &[Self]) -> Self {
+        let count = values.len() as u128;
+        let (quotients, remainders) = values.iter()
+            .fold((0, 0), |(quotients, remainders), value| (quotients + value / count, remainders + value % count));
+        quotients + remainders / count
+    }
+
+    fn run_part2(data: &[u8], result: &[InvalidNumber], config: &Config) -> anyhow::Result<()> {
+        run_part2(data, result, config)
+    }
+}
+
+impl CliNumber for i128 {
+    type Printable = i128;
+
+    fn analyze(data: &[u8], config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze_signed(data, config)
+    }
+
+    fn analyze_stream(reader: impl Read, config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze_stream_signed(reader, config)
+    }
+
+    fn analyze_reference(data: &[u8], config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze_reference_signed(data, config)
+    }
+
+    fn printable(report: &Report<Self>) -> Cow<'_, Report<Self>> {
+        Cow::Borrowed(report)
+    }
+
+    fn to_printable(&self) -> Self {
+        *self
+    }
+
+    // Same split as for u128 with division rounding towards zero, so each quotient lies between 0 and its value. Any
+    // partial sum of them then lies between the sums of the negative and of the positive values divided by the count,
+    // both in range. Each remainder takes the sign of its value and stays below the count in magnitude, the euclidean
+    // division of their sum rounds the mean down, and the mean itself is in range.
+    fn mean(values: &[Self]) -> Self {
+        let count = values.len() as i128;
+        let (quotients, remainders) = values.iter()
+            .fold((0, 0), |(quotients, remainders), value| (quotients + value / count, remainders + value % count));
+        quotients + remainders.div_euclid(count)
+    }
+}
+
+impl CliNumber for Float {
+    type Printable = Float;
+
+    fn analyze(data: &[u8], config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze_float(data, config)
+    }
+
+    fn analyze_stream(reader: impl Read, config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze_stream_float(reader, config)
+    }
+
+    fn analyze_reference(data: &[u8], config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze_reference_float(data, config)
+    }
+
+    fn printable(report: &Report<Self>) -> Cow<'_, Report<Self>> {
+        Cow::Borrowed(report)
+    }
+
+    fn to_printable(&self) -> Self {
+        *self
+    }
+
+    // A running mean, the sum could overflow to infinity. Not rounded, floats have no integer mean.
+    fn mean(values: &[Self]) -> Self {
+        let mean = values.iter().enumerate().fold(0.0, |mean, (idx, value)| mean + (value.0 - mean) / (idx + 1) as f64);
+        Float(mean)
+    }
+}
+
+// Big numbers are printed as decimal strings, most JSON parsers lose integers past 64 bits.
+#[cfg(feature = "bigint")]
+impl CliNumber for BigUint {
+    type Printable = String;
+
+    fn analyze(data: &[u8], config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze_bigint(data, config)
+    }
+
+    fn analyze_stream(reader: impl Read, config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze_stream_bigint(reader, config)
+    }
+
+    fn analyze_reference(data: &[u8], config: &Config) -> Result<Report<Self>, ProcessError> {
+        analyze_reference_bigint(data, config)
+    }
+
+    fn printable(report: &Report<Self>) -> Cow<'_, Report<String>> {
+        let invalid_numbers = report.invalid_numbers.iter()
+            .map(|invalid| InvalidNumber { value: invalid.value.to_printable(), line: invalid.line, byte_offset: invalid.byte_offset })
+            .collect();
+        let explanations = report.explanations.iter()
+            .map(|explanation| Explanation {
+                value: explanation.value.to_printable(),
+                line: explanation.line,
+                byte_offset: explanation.byte_offset,
+                addends: explanation.addends.as_ref().map(|(first, second)| (first.to_printable(), second.to_printable())),
+            })
+            .collect();
+        let valid_numbers = report.valid_numbers.iter()
+            .map(|valid| ValidNumber { value: valid.value.to_printable(), line: valid.line, byte_offset: valid.byte_offset })
+            .collect();
+        let duplicate_windows = report.duplicate_windows.iter()
+            .map(|duplicate| DuplicateWindow {
+                value: duplicate.value.to_printable(),
+                line: duplicate.line,
+                byte_offset: duplicate.byte_offset,
+                repeated: duplicate.repeated,
+            })
+            .collect();
+        let window_results = report.window_results.iter()
+            .map(|window_result| WindowResult {
+                window: window_result.window,
+                invalid_numbers: window_result.invalid_numbers.iter()
+                    .map(|invalid| InvalidNumber { value: invalid.value.to_printable(), line: invalid.line, byte_offset: invalid.byte_offset })
+                    .collect(),
+                validated: window_result.validated,
+            })
+            .collect();
+        Cow::Owned(Report {
+            invalid_numbers,
+            invalid_count: report.invalid_count,
+            parsed: report.parsed,
+            validated: report.validated,
+            explanations,
+            valid_numbers,
+            duplicate_windows,
+            phases: report.phases,
+            comparisons: report.comparisons,
+            interrupted: report.interrupted,
+            window_results,
+            chunk_profiles: report.chunk_profiles.clone(),
+        })
+    }
+
+    fn to_printable(&self) -> String {
+        self.to_string()
+    }
+
+    fn mean(values: &[Self]) -> Self {
+        values.iter().sum::<BigUint>() / values.len()
+    }
+}
+
+// Fails when the report differs from the reference, listing the invalid numbers only the report found with '-' and
+// those only the reference found with '+'. Both are in file order, so each side is looked up by byte offset.
+fn verify<T: fmt::Display + PartialEq>(report: &Report<T>, reference: &Report<T>, args: &Args) -> anyhow::Result<()> {
+    // With --max-invalid chunks stop early while the reference runs to the end, so only the invalid numbers compare.
+    let counts = |report: &Report<T>| (report.invalid_count, args.max_invalid.is_none().then_some((report.parsed, report.validated)));
+    if counts(report) == counts(reference) && report.invalid_numbers == reference.invalid_numbers && report.window_results == reference.window_results {
+        print_summary(args, format_args!("Verified against the reference implementation."));
+        return Ok(());
+    }
+
+    eprintln!("Reference: {} numbers parsed, {} validated, {} invalid", reference.parsed, reference.validated, reference.invalid_count);
+    let only_in = |invalid_numbers: &[InvalidNumber<T>], other: &[InvalidNumber<T>], sign: char| {
+        for invalid in invalid_numbers {
+            let in_other = other.binary_search_by_key(&invalid.byte_offset, |other| other.byte_offset).is_ok_and(|idx| other[idx] == *invalid);
+            if !in_other {
+                eprintln!("{} {} (line {}, byte offset {})", sign, invalid.value, invalid.line, invalid.byte_offset);
+            }
+        }
+    };
+    only_in(&report.invalid_numbers, &reference.invalid_numbers, '-');
+    only_in(&reference.invalid_numbers, &report.invalid_numbers, '+');
+    for (window_result, reference_result) in report.window_results.iter().zip(&reference.window_results) {
+        if window_result != reference_result {
+            eprintln!("Window {}: reference validated {}, {} invalid", reference_result.window, reference_result.validated, reference_result.invalid_numbers.len());
+            only_in(&window_result.invalid_numbers, &reference_result.invalid_numbers, '-');
+            only_in(&reference_result.invalid_numbers, &window_result.invalid_numbers, '+');
+        }
+    }
+    bail!("Result differs from the reference implementation")
+}
+
+// With --unique only the first occurrence of each invalid value is kept, across all chunks since the report is merged.
+// The counts still cover every occurrence.
+fn keep_unique<T: Eq + Hash + Clone>(mut report: Report<T>, args: &Args) -> Report<T> {
+    if args.unique {
+        let mut seen = HashSet::new();
+        report.invalid_numbers.retain(|invalid| seen.insert(invalid.value.clone()));
+    }
+    report
+}
 
-    let mut previous_left_bound = 0;
-    for num_core in 1..=parallelism {
-        let left_bound = previous_left_bound;
-        let (right_bound, right_bound_overflow) = get_right_bounds(&mmap, num_core * bytes_per_chunk);
-        previous_left_bound = right_bound + MARGIN_AVOID_LINE_BREAK;
-        bounds.push((left_bound, right_bound_overflow))
+// With --sort the listed numbers are printed in ascending order of value instead of file order, equal values keeping
+// their file order. Only the printed copy is sorted, verification and part two still see the file order.
+fn sort_by_value<'a, T: Ord + Clone>(report: &'a Report<T>, args: &Args) -> Cow<'a, Report<T>> {
+    if !args.sort {
+        return Cow::Borrowed(report);
     }
+    let mut sorted = report.clone();
+    sorted.invalid_numbers.sort_by(|first, second| first.value.cmp(&second.value));
+    sorted.explanations.sort_by(|first, second| first.value.cmp(&second.value));
+    Cow::Owned(sorted)
+}
 
-    bounds
+// Computed on the listed numbers, so with --unique each value counts once. None when nothing is listed.
+fn stats<T: CliNumber>(report: &Report<T>, args: &Args) -> Option<Stats<T::Printable>> {
+    if !args.stats {
+        return None;
+    }
+    let values: Vec<T> = report.invalid_numbers.iter().map(|invalid| invalid.value.clone()).collect();
+    Some(Stats {
+        count: values.len(),
+        min: values.iter().min()?.to_printable(),
+        max: values.iter().max()?.to_printable(),
+        mean: T::mean(&values).to_printable(),
+    })
 }
 
-// Calculate the next valid index within the bounds, considering an overflow of ITEM_RANGE_SIZE (100) items.
-// This ensures the first ITEM_RANGE_SIZE items of each segment are processed.
-fn get_right_bounds(mmap: &Mmap, ini_pos: usize) -> (usize, usize) {
-    let file_len = mmap.len();
-    let mut idx = ini_pos;
+// Empty inputs simply have no invalid numbers.
+fn allow_empty<T: Default>(result: Result<Report<T>, ProcessError>) -> Result<Report<T>, ProcessError> {
+    match result {
+        Err(ProcessError::EmptyInput) => Ok(Report::default()),
+        result => result,
+    }
+}
 
-    let mut right_bound = file_len - MARGIN_AVOID_LINE_BREAK;
-    let mut right_bound_overflow = right_bound;
+fn print_report<T: fmt::Display + Serialize>(report: &Report<T>, stats: Option<&Stats<T>>, timing: Timing, file_name: Option<&str>, args: &Args, mut output: Option<&mut BufWriter<File>>) -> anyhow::Result<()> {
+    debug!(elapsed_micros = timing.elapsed.as_micros() as u64, bytes = timing.bytes, "Analysis finished");
+    if args.first_only {
+        return print_first_invalid(report, file_name, args, output);
+    }
 
-    let mut overflow_count = 0;
-    while idx < file_len && overflow_count < ITEM_RANGE_SIZE {
-        if mmap[idx] == SPLIT_MARKER {
-            overflow_count += 1;
-            match overflow_count {
-                1 => { right_bound = idx - 1; }
-                ITEM_RANGE_SIZE => { right_bound_overflow = idx - 1 }
-                _ => {}
+    let result = &report.invalid_numbers;
+    match args.format {
+        OutputFormat::Text => {
+            if report.interrupted {
+                print_summary(args, format_args!("Interrupted, the results only cover part of the input."));
+            }
+            if args.time != TimeUnit::None {
+                print_summary(args, format_args!("{} ({})", args.time.format(timing.elapsed), timing.throughput()));
+            }
+            if args.time_breakdown {
+                let phases = report.phases.unwrap_or_default();
+                print_summary(args, format_args!("Load {}, bounds {}, compute {}",
+                    args.time.format(timing.load), args.time.format(phases.bounds), args.time.format(phases.compute)));
+            }
+            if let Some(comparisons) = report.comparisons {
+                print_summary(args, format_args!("{} pair checks.", comparisons));
+            }
+            if args.profile_chunks {
+                print_chunk_profiles(&report.chunk_profiles, args);
+            }
+            if let (Some(largest), false) = (args.windows.last(), report.window_results.is_empty()) {
+                print_summary(args, format_args!("Window {}:", largest));
+            }
+            print_summary(args, format_args!("{} invalid numbers found.", report.invalid_count));
+            if args.unique {
+                print_summary(args, format_args!("{} distinct invalid values.", result.len()));
+            }
+            if args.emit != Emit::Invalid {
+                print_summary(args, format_args!("{} valid numbers listed.", report.valid_numbers.len()));
+            }
+            if args.sort {
+                print_summary(args, format_args!("Listed in ascending order of value, not in file order."));
+            }
+            match stats {
+                Some(Stats { count, min, max, mean }) =>
+                    print_summary(args, format_args!("Stats: {} invalid numbers, min {}, max {}, mean {}", count, min, max, mean)),
+                None if args.stats => print_summary(args, format_args!("Stats: no invalid numbers")),
+                None => {}
+            }
+            if args.count_only {
+                print_summary(args, format_args!("{} numbers parsed, {} validated, {} valid, {} invalid",
+                    report.parsed, report.validated, report.valid(), report.invalid_count));
+            } else {
+                let first_invalid = report.first_invalid().map_or_else(|| "none".to_string(), |invalid| invalid.value.to_string());
+                print_summary(args, format_args!("{} numbers parsed, {} validated, {} valid, {} invalid, first invalid: {}",
+                    report.parsed, report.validated, report.valid(), report.invalid_count, first_invalid));
+            }
+            match output.as_deref_mut() {
+                Some(output) if args.explain => {
+                    for explanation in &report.explanations {
+                        writeln!(output, "{}", format_explanation(explanation))?;
+                    }
+                }
+                Some(output) => {
+                    for (number, is_valid) in emitted(report, args.emit) {
+                        writeln!(output, "{}{}", number.value, emit_label(is_valid, args))?;
+                    }
+                }
+                None if args.explain => {
+                    for explanation in &report.explanations {
+                        println!("{} (line {}, byte offset {})", format_explanation(explanation), explanation.line, explanation.byte_offset);
+                    }
+                }
+                None => {
+                    for (number, is_valid) in emitted(report, args.emit) {
+                        println!("{}{} (line {}, byte offset {})", number.value, emit_label(is_valid, args), number.line, number.byte_offset);
+                    }
+                }
+            }
+            for window_result in &report.window_results {
+                print_summary(args, format_args!("Window {}: {} invalid numbers found, {} validated.",
+                    window_result.window, window_result.invalid_numbers.len(), window_result.validated));
+                for invalid in &window_result.invalid_numbers {
+                    match output.as_deref_mut() {
+                        Some(output) => writeln!(output, "{}", invalid.value)?,
+                        None => println!("{} (line {}, byte offset {})", invalid.value, invalid.line, invalid.byte_offset),
+                    }
+                }
+            }
+            // Diagnostics rather than results, so they stay out of --output.
+            if args.report_dupes {
+                print_summary(args, format_args!("{} windows with duplicate values.", report.duplicate_windows.len()));
+                for duplicate in &report.duplicate_windows {
+                    print_summary(args, format_args!("{} has {} repeated values in its window (line {}, byte offset {})",
+                        duplicate.value, duplicate.repeated, duplicate.line, duplicate.byte_offset));
+                }
             }
         }
-        idx += 1;
+        OutputFormat::Json => {
+            let json = serde_json::to_string(&JsonOutput {
+                file: file_name,
+                count: report.invalid_count,
+                parsed: report.parsed,
+                validated: report.validated,
+                valid: report.valid(),
+                first_invalid: report.first_invalid().map(|invalid| &invalid.value),
+                distinct: args.unique.then_some(result.len()),
+                stats,
+                elapsed_micros: timing.elapsed.as_micros(),
+                time_breakdown: args.time_breakdown.then(|| {
+                    let phases = report.phases.unwrap_or_default();
+                    JsonTimeBreakdown {
+                        load_micros: timing.load.as_micros(),
+                        bounds_micros: phases.bounds.as_micros(),
+                        compute_micros: phases.compute.as_micros(),
+                    }
+                }),
+                comparisons: report.comparisons,
+                invalid_numbers: (args.emit != Emit::Valid).then_some(&result[..]),
+                valid_numbers: (args.emit != Emit::Invalid).then_some(&report.valid_numbers[..]),
+                explanations: args.explain.then_some(&report.explanations[..]),
+                duplicate_windows: args.report_dupes.then_some(&report.duplicate_windows[..]),
+                interrupted: report.interrupted.then_some(true),
+                windows: (!report.window_results.is_empty()).then_some(&report.window_results[..]),
+                chunks: args.profile_chunks.then(|| report.chunk_profiles.iter()
+                    .map(|profile| JsonChunkProfile {
+                        start: profile.byte_range.start,
+                        end: profile.byte_range.end,
+                        records: profile.records,
+                        elapsed_micros: profile.elapsed.as_micros(),
+                    })
+                    .collect()),
+            })?;
+            match output {
+                Some(output) => writeln!(output, "{}", json)?,
+                None => println!("{}", json),
+            }
+        }
+        OutputFormat::Ndjson if args.explain => write_ndjson(report.explanations.iter().map(|explanation| (explanation, None)), file_name, output)?,
+        OutputFormat::Ndjson => {
+            let valid = |is_valid| (args.emit == Emit::Both).then_some(is_valid);
+            write_ndjson(emitted(report, args.emit).into_iter().map(|(number, is_valid)| (number, valid(is_valid))), file_name, output)?
+        }
     }
+    Ok(())
+}
 
-    (right_bound, right_bound_overflow)
+// Numbers listed by --emit, each with whether it is valid. Both lists are merged back into file order, a single one
+// keeps its own order, which --sort may have changed for the invalid numbers.
+fn emitted<T>(report: &Report<T>, emit: Emit) -> Vec<(&InvalidNumber<T>, bool)> {
+    let invalid = report.invalid_numbers.iter().filter(|_| emit != Emit::Valid).map(|invalid| (invalid, false));
+    let valid = report.valid_numbers.iter().filter(|_| emit != Emit::Invalid).map(|valid| (valid, true));
+    let mut listed: Vec<_> = invalid.chain(valid).collect();
+    if emit == Emit::Both {
+        listed.sort_by_key(|(number, _)| number.byte_offset);
+    }
+    listed
 }
 
+// Each number is serialized straight to the writer, so large results never build a single JSON string.
+fn write_ndjson<'a, N: Serialize + 'a>(numbers: impl IntoIterator<Item = (&'a N, Option<bool>)>, file_name: Option<&str>, output: Option<&mut BufWriter<File>>) -> anyhow::Result<()> {
+    let mut writer = ndjson_writer(output);
+    for (number, valid) in numbers {
+        write_ndjson_line(&mut writer, file_name, valid, number)?;
+    }
+    Ok(())
+}
+
+// Stdout is line buffered, so each line reaches a consumer as soon as it is written.
+fn ndjson_writer(output: Option<&mut BufWriter<File>>) -> Box<dyn Write + '_> {
+    match output {
+        Some(output) => Box::new(output),
+        None => Box::new(io::stdout().lock()),
+    }
+}
 
-fn process(mmap: &Mmap, left_bound: usize, right_bound: usize) -> Vec<u128> {
+fn write_ndjson_line<N: Serialize>(writer: &mut impl Write, file_name: Option<&str>, valid: Option<bool>, number: &N) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *writer, &NdjsonLine { file: file_name, valid, number })?;
+    writeln!(writer)?;
+    Ok(())
+}
 
-    // Max length of u128 represented as str
-    let mut str_buffer: [u8; STR_U128_LEN] = [0; STR_U128_LEN];
-    let mut str_buffer_idx = STR_U128_LEN;
+// One row per chunk in input order, then how far the slowest chunk is from the mean, which is what holds up the run.
+fn print_chunk_profiles(profiles: &[ChunkProfile], args: &Args) {
+    let Some((slowest_idx, slowest)) = profiles.iter().enumerate().max_by_key(|(_, profile)| profile.elapsed) else {
+        return;
+    };
+    print_summary(args, format_args!("{:>5}  {:>23}  {:>10}  elapsed", "chunk", "bytes", "records"));
+    for (idx, profile) in profiles.iter().enumerate() {
+        let bytes = format!("{}..{}", profile.byte_range.start, profile.byte_range.end);
+        print_summary(args, format_args!("{:>5}  {:>23}  {:>10}  {}", idx, bytes, profile.records, args.time.format(profile.elapsed)));
+    }
+    let mean = profiles.iter().map(|profile| profile.elapsed).sum::<Duration>() / profiles.len() as u32;
+    let ratio = if mean.is_zero() { 1.0 } else { slowest.elapsed.as_secs_f64() / mean.as_secs_f64() };
+    print_summary(args, format_args!("Slowest chunk {} took {:.2}x the mean of {}.", slowest_idx, ratio, args.time.format(mean)));
+}
 
-    // Used fixed array instead of VecDeque because read operations are more intensive than insertions.
-    let mut numbers: [u128; NUMBERS_BUFFER_SIZE] = [0; NUMBERS_BUFFER_SIZE];
-    let mut numbers_idx = 0;
+// Only the value is printed, none when every number is valid.
+fn print_first_invalid<T: fmt::Display + Serialize>(report: &Report<T>, file_name: Option<&str>, args: &Args, output: Option<&mut BufWriter<File>>) -> anyhow::Result<()> {
+    let first_invalid = report.first_invalid().map(|invalid| &invalid.value);
+    let line = match args.format {
+        OutputFormat::Text => first_invalid.map_or_else(|| "none".to_string(), |value| value.to_string()),
+        OutputFormat::Json => serde_json::to_string(&JsonFirstOutput { file: file_name, first_invalid })?,
+        OutputFormat::Ndjson => return write_ndjson(report.invalid_numbers.iter().map(|invalid| (invalid, None)), file_name, output),
+    };
+    match output {
+        Some(output) => writeln!(output, "{}", line)?,
+        None => println!("{}", line),
+    }
+    Ok(())
+}
+
+// Only needed to tell the two lists apart with --emit both.
+fn emit_label(is_valid: bool, args: &Args) -> &'static str {
+    match args.emit {
+        Emit::Both if is_valid => " VALID",
+        Emit::Both => " INVALID",
+        _ => "",
+    }
+}
+
+fn format_explanation<T: fmt::Display>(explanation: &Explanation<T>) -> String {
+    match &explanation.addends {
+        Some((first, second)) => format!("{} = {} + {}", explanation.value, first, second),
+        None => format!("{} INVALID", explanation.value),
+    }
+}
+
+
+// Summary lines move to stderr when the results themselves are written to --output.
+fn print_summary(args: &Args, line: fmt::Arguments) {
+    if args.output.is_some() {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
 
-    let mut result = Vec::new();
-    for &byte in mmap[left_bound..=right_bound].iter().rev() {
-        if byte != SPLIT_MARKER {
-            str_buffer_idx -= 1;
-            str_buffer[str_buffer_idx] = byte;
-            continue;
+fn flush_output(output: Option<BufWriter<File>>) -> anyhow::Result<()> {
+    if let Some(mut output) = output {
+        output.flush().context("Failed to write output")?;
+    }
+    Ok(())
+}
+
+
+// Diagnostics go to stderr at info level unless RUST_LOG says otherwise, results are still printed to stdout.
+fn init_logging() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_LEVEL));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .without_time()
+        .with_target(false)
+        .init();
+}
+
+// Positional arguments are input paths, every other argument must be a known flag.
+fn parse_args() -> anyhow::Result<Args> {
+    let mut args = Args::default();
+
+    let mut raw_args = env::args_os().skip(1);
+    while let Some(arg) = raw_args.next() {
+        match arg.to_str() {
+            Some(SKIP_INVALID_TOKENS_FLAG) => args.skip_invalid_tokens = true,
+            Some(PART2_FLAG) => args.part2 = true,
+            Some(FORMAT_FLAG) => args.format = next_value(&mut raw_args, FORMAT_FLAG)?.parse()?,
+            Some(THREADS_FLAG) => args.threads = parse_value(&mut raw_args, THREADS_FLAG)?,
+            Some(PROGRESS_FLAG) => args.progress = true,
+            Some(OUTPUT_FLAG) => args.output = Some(PathBuf::from(next_value(&mut raw_args, OUTPUT_FLAG)?)),
+            Some(SIGNED_FLAG) => args.signed = true,
+            Some(EXPLAIN_FLAG) => args.explain = true,
+            Some(STREAM_FLAG) => args.stream = true,
+            Some(FIRST_ONLY_FLAG) => args.first_only = true,
+            Some(ALLOW_UNDERSCORES_FLAG) => args.allow_underscores = true,
+            Some(NO_ZERO_PAIR_FLAG) => args.no_zero_pair = true,
+            Some(COUNT_ONLY_FLAG) => args.count_only = true,
+            Some(PARALLEL_PAIRS_FLAG) => args.parallel_pairs = true,
+            Some(SORTED_WINDOW_FLAG) => args.sorted_window = true,
+            Some(VERIFY_FLAG) => args.verify = true,
+            Some(BIGINT_FLAG) => args.bigint = true,
+            Some(UNIQUE_FLAG) => args.unique = true,
+            Some(SORT_FLAG) => args.sort = true,
+            Some(DETERMINISTIC_FLAG) => args.deterministic = true,
+            Some(DEBUG_COUNTERS_FLAG) => args.debug_counters = true,
+            Some(REPORT_DUPES_FLAG) => args.report_dupes = true,
+            Some(INTERRUPTIBLE_FLAG) => args.interruptible = true,
+            Some(EMIT_FLAG) => args.emit = next_value(&mut raw_args, EMIT_FLAG)?.parse()?,
+            Some(STATS_FLAG) => args.stats = true,
+            Some(OFFSET_FLAG) => args.offset = Some(parse_value(&mut raw_args, OFFSET_FLAG)?),
+            Some(LENGTH_FLAG) => args.length = Some(parse_value(&mut raw_args, LENGTH_FLAG)?),
+            Some(MAX_MEMORY_FLAG) => args.max_memory = Some(parse_value(&mut raw_args, MAX_MEMORY_FLAG)?),
+            Some(SKIP_HEADER_FLAG) => args.skip_header = parse_value(&mut raw_args, SKIP_HEADER_FLAG)?,
+            Some(RADIX_FLAG) => args.radix = parse_value(&mut raw_args, RADIX_FLAG)?,
+            Some(TIME_BREAKDOWN_FLAG) => args.time_breakdown = true,
+            Some(WINDOW_FROM_HEADER_FLAG) => args.window_from_header = true,
+            Some(FLOAT_FLAG) => args.float = true,
+            Some(EPSILON_FLAG) => args.epsilon = Some(parse_value(&mut raw_args, EPSILON_FLAG)?),
+            Some(RETRY_OPEN_FLAG) => args.retry_open = parse_value(&mut raw_args, RETRY_OPEN_FLAG)?,
+            Some(MAX_INVALID_FLAG) => args.max_invalid = Some(parse_value(&mut raw_args, MAX_INVALID_FLAG)?),
+            Some(RECORDS_PER_CHUNK_FLAG) => args.records_per_chunk = Some(parse_value(&mut raw_args, RECORDS_PER_CHUNK_FLAG)?),
+            Some(EXPECTED_INVALID_FLAG) => args.expected_invalid = Some(parse_value(&mut raw_args, EXPECTED_INVALID_FLAG)?),
+            Some(VERIFY_BOUNDS_FLAG) => args.verify_bounds = true,
+            Some(PROFILE_CHUNKS_FLAG) => args.profile_chunks = true,
+            Some(WINDOW_FLAG) => args.windows = parse_windows(&next_value(&mut raw_args, WINDOW_FLAG)?)?,
+            Some(TIME_FLAG) => args.time = next_value(&mut raw_args, TIME_FLAG)?.parse()?,
+            Some(WHITESPACE_FLAG) => args.whitespace = true,
+            Some(DELIMITER_FLAG) => (args.delimiter, args.crlf) = parse_delimiter(&next_value(&mut raw_args, DELIMITER_FLAG)?)?,
+            Some(COMMENT_FLAG) => args.comment = Some(parse_comment_prefix(&next_value(&mut raw_args, COMMENT_FLAG)?)?),
+            Some(flag) if flag.starts_with("--") => bail!("Unknown option {}", flag),
+            _ => args.file_paths.push(arg),
         }
+    }
 
-        let new_number = parse_number_from_str_buffer(&str_buffer[str_buffer_idx..STR_U128_LEN]);
-        if numbers_idx == NUMBERS_BUFFER_SIZE {
-            process_next_number(&mut result, &mut numbers, new_number);
-        } else {
-            numbers[numbers_idx] = new_number;
-            numbers_idx += 1;
+    // The contiguous range search relies on every number being non-negative.
+    if args.signed && args.part2 {
+        bail!("{} can't be combined with {}", PART2_FLAG, SIGNED_FLAG);
+    }
+    // Part two needs the whole input in memory, which streaming avoids.
+    if args.stream && args.part2 {
+        bail!("{} can't be combined with {}", PART2_FLAG, STREAM_FLAG);
+    }
+    // Both need the invalid numbers themselves.
+    if args.count_only && args.part2 {
+        bail!("{} can't be combined with {}", PART2_FLAG, COUNT_ONLY_FLAG);
+    }
+    if args.count_only && args.first_only {
+        bail!("{} can't be combined with {}", FIRST_ONLY_FLAG, COUNT_ONLY_FLAG);
+    }
+    if args.count_only && args.unique {
+        bail!("{} can't be combined with {}", UNIQUE_FLAG, COUNT_ONLY_FLAG);
+    }
+    if args.count_only && args.stats {
+        bail!("{} can't be combined with {}", STATS_FLAG, COUNT_ONLY_FLAG);
+    }
+    // The valid numbers are listed next to the invalid ones, which these only keep part of or list differently.
+    if args.emit != Emit::Invalid && (args.count_only || args.first_only || args.explain || args.max_invalid.is_some() || args.sort) {
+        bail!("{} valid or both can't be combined with {}, {}, {}, {} or {}", EMIT_FLAG, COUNT_ONLY_FLAG, FIRST_ONLY_FLAG, EXPLAIN_FLAG, MAX_INVALID_FLAG, SORT_FLAG);
+    }
+    // Only the numbers are listed, there is no summary to hold the rest.
+    if args.format == OutputFormat::Ndjson && (args.count_only || args.stats || args.time_breakdown || args.debug_counters || args.report_dupes || args.profile_chunks) {
+        bail!("ndjson {} can't be combined with {}, {}, {}, {}, {} or {}", FORMAT_FLAG, COUNT_ONLY_FLAG, STATS_FLAG, TIME_BREAKDOWN_FLAG, DEBUG_COUNTERS_FLAG, REPORT_DUPES_FLAG, PROFILE_CHUNKS_FLAG);
+    }
+    // Only the first invalid number is printed.
+    if args.first_only && (args.report_dupes || args.profile_chunks) {
+        bail!("{} and {} can't be combined with {}", REPORT_DUPES_FLAG, PROFILE_CHUNKS_FLAG, FIRST_ONLY_FLAG);
+    }
+    if args.max_invalid.is_some() && (args.count_only || args.first_only) {
+        bail!("{} can't be combined with {} or {}", MAX_INVALID_FLAG, COUNT_ONLY_FLAG, FIRST_ONLY_FLAG);
+    }
+    if args.max_invalid == Some(0) {
+        bail!("{} must be at least 1", MAX_INVALID_FLAG);
+    }
+    if args.records_per_chunk == Some(0) {
+        bail!("{} must be at least 1", RECORDS_PER_CHUNK_FLAG);
+    }
+    // Streaming has no chunks to size.
+    if args.stream && args.records_per_chunk.is_some() {
+        bail!("{} can't be combined with {}", RECORDS_PER_CHUNK_FLAG, STREAM_FLAG);
+    }
+    // Only the chunks reserve room, and only for the invalid numbers they list.
+    if args.expected_invalid.is_some() && (args.stream || args.count_only || args.first_only) {
+        bail!("{} can't be combined with {}, {} or {}", EXPECTED_INVALID_FLAG, STREAM_FLAG, COUNT_ONLY_FLAG, FIRST_ONLY_FLAG);
+    }
+    if args.verify_bounds && args.stream {
+        bail!("{} can't be combined with {}", VERIFY_BOUNDS_FLAG, STREAM_FLAG);
+    }
+    if args.profile_chunks && args.stream {
+        bail!("{} can't be combined with {}", PROFILE_CHUNKS_FLAG, STREAM_FLAG);
+    }
+    if !args.windows.is_empty() && args.window_from_header {
+        bail!("{} can't be combined with {}", WINDOW_FLAG, WINDOW_FROM_HEADER_FLAG);
+    }
+    // Only the chunks check the smaller windows, and only while listing every invalid number.
+    if args.windows.len() > 1 && (args.stream || args.count_only || args.first_only || args.max_invalid.is_some() || args.format == OutputFormat::Ndjson) {
+        bail!("Several windows for {} can't be combined with {}, {}, {}, {} or ndjson output",
+            WINDOW_FLAG, STREAM_FLAG, COUNT_ONLY_FLAG, FIRST_ONLY_FLAG, MAX_INVALID_FLAG);
+    }
+    #[cfg(not(feature = "bigint"))]
+    if args.bigint {
+        bail!("Built without bigint support, rebuild with --features bigint to use {}", BIGINT_FLAG);
+    }
+    if args.signed && args.bigint {
+        bail!("{} can't be combined with {}", BIGINT_FLAG, SIGNED_FLAG);
+    }
+    // Part two works on u128 numbers.
+    if args.bigint && args.part2 {
+        bail!("{} can't be combined with {}", PART2_FLAG, BIGINT_FLAG);
+    }
+    // Floats may be negative and have no other width, and are always decimal.
+    if args.float && (args.signed || args.bigint || args.part2) {
+        bail!("{} can't be combined with {}, {} or {}", FLOAT_FLAG, SIGNED_FLAG, BIGINT_FLAG, PART2_FLAG);
+    }
+    if args.float && args.radix != DEFAULT_RADIX {
+        bail!("{} can't be combined with {}", FLOAT_FLAG, RADIX_FLAG);
+    }
+    if args.epsilon.is_some() && !args.float {
+        bail!("{} only applies with {}", EPSILON_FLAG, FLOAT_FLAG);
+    }
+    if args.epsilon.is_some_and(|epsilon| epsilon.is_nan() || epsilon < 0.0) {
+        bail!("{} must be a non-negative number", EPSILON_FLAG);
+    }
+    // The reference needs the whole input in memory, and streaming is single-threaded already.
+    if args.stream && args.verify {
+        bail!("{} can't be combined with {}", VERIFY_FLAG, STREAM_FLAG);
+    }
+    // The range is cut out of the input in memory, the stream never holds it as a whole.
+    if args.stream && (args.offset.is_some() || args.length.is_some()) {
+        bail!("{} and {} can't be combined with {}", OFFSET_FLAG, LENGTH_FLAG, STREAM_FLAG);
+    }
+    // Streaming reads and computes block by block, with no bounds to compute.
+    if args.stream && args.time_breakdown {
+        bail!("{} can't be combined with {}", TIME_BREAKDOWN_FLAG, STREAM_FLAG);
+    }
+    // The forward scan of the stream never tracks the repeated values of its window.
+    if args.stream && args.report_dupes {
+        bail!("{} can't be combined with {}", REPORT_DUPES_FLAG, STREAM_FLAG);
+    }
+    // Golden runs go through a single chunk on a single thread and list the numbers in file order.
+    if args.deterministic {
+        if args.threads != 0 || args.parallel_pairs || args.sort || args.records_per_chunk.is_some() {
+            bail!("{} can't be combined with {}, {}, {} or {}", DETERMINISTIC_FLAG, THREADS_FLAG, PARALLEL_PAIRS_FLAG, SORT_FLAG, RECORDS_PER_CHUNK_FLAG);
         }
+        args.threads = 1;
+    }
+
+    Ok(args)
+}
+
+
+fn next_value(raw_args: &mut impl Iterator<Item = OsString>, flag: &str) -> anyhow::Result<String> {
+    raw_args.next()
+        .with_context(|| format!("Missing value for {}", flag))?
+        .into_string()
+        .map_err(|value| anyhow::anyhow!("Value {:?} for {} is not valid UTF-8", value, flag))
+}
+
+fn parse_value<T: FromStr>(raw_args: &mut impl Iterator<Item = OsString>, flag: &str) -> anyhow::Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    let value = next_value(raw_args, flag)?;
+    value.parse().with_context(|| format!("Invalid value '{}' for {}", value, flag))
+}
 
-        // Reset
-        str_buffer[str_buffer_idx..STR_U128_LEN].fill(0);
-        str_buffer_idx = STR_U128_LEN;
+
+// Chunk bounds step over exactly one delimiter byte, so multi-byte delimiters are rejected.
+// The delimiter byte and whether records end on "\r\n", the only delimiter longer than a byte.
+fn parse_delimiter(value: &str) -> anyhow::Result<(u8, bool)> {
+    match value {
+        "\\n" => Ok((b'\n', false)),
+        "\\t" => Ok((b'\t', false)),
+        "\\r\\n" | "\r\n" => Ok((b'\n', true)),
+        _ if value.len() == 1 => Ok((value.as_bytes()[0], false)),
+        _ => bail!("Delimiter must be a single byte or \\r\\n, got '{}'", value),
+    }
+}
+
+// Comma-separated window sizes, returned ascending without repeats.
+fn parse_windows(value: &str) -> anyhow::Result<Vec<usize>> {
+    let mut windows = value.split(',')
+        .map(|window| usize::from_str(window.trim()).with_context(|| format!("{} takes comma-separated positive integers, got '{}'", WINDOW_FLAG, value)))
+        .collect::<anyhow::Result<Vec<usize>>>()?;
+    if windows.contains(&0) {
+        bail!("{} must be greater than 0", WINDOW_FLAG);
+    }
+    windows.sort_unstable();
+    windows.dedup();
+    Ok(windows)
+}
+
+// Comments are only told apart by their first non-blank byte.
+fn parse_comment_prefix(value: &str) -> anyhow::Result<u8> {
+    match value.as_bytes() {
+        [prefix] => Ok(*prefix),
+        _ => bail!("Comment prefix must be a single byte, got '{}'", value),
     }
+}
 
-    let new_number = parse_number_from_str_buffer(&str_buffer[str_buffer_idx..STR_U128_LEN]);
-    process_next_number(&mut result, &mut numbers, new_number);
 
-    if !is_number_valid(numbers[0], &numbers[1..=ITEM_RANGE_SIZE]) {
-        result.push(numbers[0]);
+// An explicit thread count also sizes rayon's global pool, auto keeps rayon's default pool.
+// Without the parallel feature the count only sets how many chunks the input is split into.
+fn resolve_parallelism(threads: usize) -> anyhow::Result<usize> {
+    if threads == 0 {
+        let cores = match available_parallelism() {
+            Ok(cores) => cores.get(),
+            Err(err) => {
+                warn!("Couldn't detect the core count ({}), using {} threads", err, MIN_PARALLELISM);
+                MIN_PARALLELISM
+            }
+        };
+        return Ok(max(MIN_PARALLELISM, cores));
     }
 
-    result
+    let parallelism = max(1, threads);
+    #[cfg(parallel)]
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(parallelism)
+        .build_global()
+        .context("Failed to configure the thread pool")?;
+    Ok(parallelism)
 }
 
 
-fn process_next_number(result: &mut Vec<u128>, numbers: &mut [u128; NUMBERS_BUFFER_SIZE], new_number: u128) {
-    if !is_number_valid(numbers[0], &numbers[1..=ITEM_RANGE_SIZE]) {
-        result.push(numbers[0]);
+// Part two: find the contiguous range summing to the first invalid number and print min + max of it.
+fn run_part2(data: &[u8], result: &[InvalidNumber], config: &Config) -> anyhow::Result<()> {
+    let Some(target) = result.first().map(|invalid| invalid.value) else {
+        println!("No invalid number to search a contiguous range for.");
+        return Ok(());
+    };
+
+    let numbers = parse_numbers(data, config)?;
+    match find_contiguous_range_sum(&numbers, target) {
+        Some((min, max)) => println!("Contiguous range summing to {}: min {}, max {}, min + max = {}", target, min, max, min + max),
+        None => println!("No contiguous range sums to {}.", target),
     }
+    Ok(())
+}
 
-    numbers.rotate_left(1);
-    numbers[ITEM_RANGE_SIZE] = new_number;
+
+// Reads from stdin when the argument is "-", or when no argument is given and stdin is not a TTY.
+fn reads_stdin(arg: &Option<OsString>) -> bool {
+    match arg {
+        Some(arg) => arg == STDIN_ARG,
+        None => !io::stdin().is_terminal(),
+    }
 }
 
-fn parse_number_from_str_buffer(str_buffer: &[u8]) -> u128 {
-    let number_str = std::str::from_utf8(str_buffer).expect("Parse byte buffer to str successfully");
-    u128::from_str(number_str).expect("Parse number from str successfully")
+// Same sources as load_input, without loading anything up front.
+fn open_stream(arg: Option<OsString>, retries: u32) -> anyhow::Result<Box<dyn Read>> {
+    if reads_stdin(&arg) {
+        return Ok(Box::new(io::stdin()));
+    }
+    if let Some(url) = input_url(&arg) {
+        return Ok(Box::new(open_url(url)?));
+    }
+
+    let (file_path, file) = open_file(arg, retries)?;
+    info!("Reading {} as a stream", file_path.display());
+    match Compression::of(&file_path) {
+        Compression::None => Ok(Box::new(file)),
+        compression => compression.decoder(file),
+    }
 }
 
+// Compression of an input file, told by its extension alone.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+}
 
-// Skip all numbers greater than the target (excluding the target itself). The target and 0 may still be valid candidates together.
-fn is_number_valid(target: u128, candidates: &[u128]) -> bool {
-    candidates.iter()
-        .enumerate()
-        .filter(|(idx, &outer_ref)| outer_ref <= target)
-        .any(|(idx, &outer_ref)| {
-            candidates.iter()
-                .skip(idx + 1)
-                .any(|&inner_ref| inner_ref + outer_ref == target)
+impl Compression {
+    fn of(file_path: &Path) -> Self {
+        match file_path.extension() {
+            Some(extension) if extension == GZIP_EXTENSION => Compression::Gzip,
+            Some(extension) if extension == ZSTD_EXTENSION => Compression::Zstd,
+            Some(extension) if extension == BZIP2_EXTENSION => Compression::Bzip2,
+            _ => Compression::None,
+        }
+    }
+
+    fn decoder(self, file: File) -> anyhow::Result<Box<dyn Read>> {
+        Ok(match self {
+            Compression::None => Box::new(file),
+            Compression::Gzip => Box::new(GzDecoder::new(file)),
+            Compression::Zstd => Box::new(zstd_decoder(file)?),
+            Compression::Bzip2 => Box::new(bzip2_decoder(file)?),
         })
+    }
+}
+
+// Arguments with an http:// or https:// scheme are downloaded instead of opened, and taken as plaintext whatever
+// their extension.
+fn input_url(arg: &Option<OsString>) -> Option<&str> {
+    arg.as_ref()?.to_str().filter(|arg| arg.starts_with("http://") || arg.starts_with("https://"))
+}
+
+#[cfg(feature = "http")]
+fn open_url(url: &str) -> anyhow::Result<impl Read> {
+    info!("Downloading {}", url);
+    let response = ureq::get(url).call().with_context(|| format!("Failed to fetch {}", url))?;
+    Ok(response.into_body().into_reader())
+}
+
+// Without the feature a URL would only fail as a missing file, so it is rejected with the reason.
+#[cfg(not(feature = "http"))]
+fn open_url(_url: &str) -> anyhow::Result<io::Empty> {
+    bail!("Built without http support, rebuild with --features http to read URLs")
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decoder(file: File) -> anyhow::Result<impl Read> {
+    zstd::Decoder::new(file).context("Failed to initialize the zstd decoder")
+}
+
+// Without the feature a compressed input would fail on its first token, so it is rejected up front.
+#[cfg(not(feature = "zstd"))]
+fn zstd_decoder(_file: File) -> anyhow::Result<io::Empty> {
+    bail!("Built without zstd support, rebuild with --features zstd to read .{} inputs", ZSTD_EXTENSION)
+}
+
+#[cfg(feature = "bzip2")]
+fn bzip2_decoder(file: File) -> anyhow::Result<impl Read> {
+    Ok(bzip2::read::BzDecoder::new(file))
+}
+
+#[cfg(not(feature = "bzip2"))]
+fn bzip2_decoder(_file: File) -> anyhow::Result<io::Empty> {
+    bail!("Built without bzip2 support, rebuild with --features bzip2 to read .{} inputs", BZIP2_EXTENSION)
+}
+
+// Chunks are scanned backwards, which the kernel's forward readahead doesn't anticipate, so the whole mapping is
+// prefetched instead. The advice is only a hint, a failure leaves page faults as they were.
+#[cfg(all(unix, feature = "mmap"))]
+fn advise_will_need(mmap: &Mmap) {
+    unsafe {
+        libc::madvise(mmap.as_ptr() as *mut libc::c_void, mmap.len(), libc::MADV_WILLNEED);
+    }
+}
+
+#[cfg(all(not(unix), feature = "mmap"))]
+fn advise_will_need(_mmap: &Mmap) {}
+
+// Stdin is read into memory, otherwise mmaps the input file.
+fn load_input(arg: Option<OsString>, max_memory: Option<usize>, retries: u32) -> anyhow::Result<Input> {
+    if reads_stdin(&arg) {
+        let buffer = read_all(io::stdin().lock(), max_memory).context("Failed to read stdin")?;
+        return Ok(buffer.into());
+    }
+    // The body is read straight into the buffer the pipeline runs on, nothing is written to disk.
+    if let Some(url) = input_url(&arg) {
+        let buffer = read_all(open_url(url)?, max_memory).with_context(|| format!("Failed to download {}", url))?;
+        return Ok(buffer.into());
+    }
+
+    let (file_path, file) = open_file(arg, retries)?;
+
+    // Compressed inputs are decompressed into memory, plaintext keeps the zero-copy mmap path.
+    let compression = Compression::of(&file_path);
+    if compression != Compression::None {
+        let buffer = read_all(compression.decoder(file)?, max_memory)
+            .with_context(|| format!("Failed to decompress {}", file_path.display()))?;
+        return Ok(buffer.into());
+    }
+
+    // Zero-length files can't be mapped.
+    if file.metadata()?.len() == 0 {
+        return Ok(Vec::new().into());
+    }
+    map_file(&file_path, file, max_memory)
+}
+
+// Some filesystems and special files can't be mapped, those are read into memory instead.
+#[cfg(feature = "mmap")]
+fn map_file(file_path: &Path, file: File, max_memory: Option<usize>) -> anyhow::Result<Input> {
+    match unsafe { MmapOptions::new().map(&file) } {
+        Ok(mmap) => {
+            info!("Reading {} through mmap", file_path.display());
+            advise_will_need(&mmap);
+            Ok(Input::Mmap(mmap))
+        }
+        Err(err) => {
+            warn!("Reading {} through a buffered reader, mmap failed: {}", file_path.display(), err);
+            read_file(file_path, file, max_memory)
+        }
+    }
+}
+
+#[cfg(not(feature = "mmap"))]
+fn map_file(file_path: &Path, file: File, max_memory: Option<usize>) -> anyhow::Result<Input> {
+    info!("Reading {} through a buffered reader", file_path.display());
+    read_file(file_path, file, max_memory)
+}
+
+fn read_file(file_path: &Path, file: File, max_memory: Option<usize>) -> anyhow::Result<Input> {
+    let buffer = read_all(BufReader::new(file), max_memory)
+        .with_context(|| format!("Failed to read {}", file_path.display()))?;
+    Ok(buffer.into())
+}
+
+// Inputs read into memory fail once they outgrow max_memory instead of exhausting it, the mmap is left to the page cache.
+fn read_all(reader: impl Read, max_memory: Option<usize>) -> anyhow::Result<Vec<u8>> {
+    let limit = max_memory.map_or(u64::MAX, |max_memory| max_memory as u64);
+    let mut buffer = Vec::new();
+    reader.take(limit.saturating_add(1)).read_to_end(&mut buffer)?;
+    if buffer.len() as u64 > limit {
+        bail!("Input is larger than the {} bytes of {}, {} reads it in bounded blocks", limit, MAX_MEMORY_FLAG, STREAM_FLAG);
+    }
+    Ok(buffer)
+}
+
+// The first positional argument is taken as the input path, relative paths being resolved against the current directory.
+// Falls back to RELATIVE_FILE_PATH under the current directory when no argument is given.
+// Retries cover a file that doesn't exist yet as well as one that can't be opened yet, e.g. while it is still being
+// written, with the last error returned once they run out.
+fn open_file(arg: Option<OsString>, retries: u32) -> anyhow::Result<(PathBuf, File)> {
+    let open = || {
+        let file_path = resolve_file_path(arg.clone())?;
+        let file = File::open(&file_path).with_context(|| format!("Failed to open {}", file_path.display()))?;
+        anyhow::Ok((file_path, file))
+    };
+    let mut delay = RETRY_OPEN_DELAY;
+    for attempt in 1..=retries {
+        match open() {
+            Ok(opened) => return Ok(opened),
+            Err(err) => {
+                warn!("{:#}, retrying in {:?} ({}/{})", err, delay, attempt, retries);
+                thread::sleep(delay);
+                delay = (delay * 2).min(RETRY_OPEN_MAX_DELAY);
+            }
+        }
+    }
+    open()
+}
+
+fn resolve_file_path(arg: Option<OsString>) -> anyhow::Result<PathBuf> {
+    let current_dir = env::current_dir()?;
+    let file_path = match arg {
+        Some(arg) => current_dir.join(arg),
+        None => RELATIVE_FILE_PATH.iter().fold(current_dir, |path, segment| path.join(segment)),
+    };
+
+    if !file_path.is_file() {
+        bail!("Input file {} does not exist", file_path.display());
+    }
+
+    Ok(file_path)
+}
+
+// The window size is read from the WINDOW_SIZE environment variable, defaulting to DEFAULT_ITEM_RANGE_SIZE.
+// --window takes precedence over it, and --window-from-header replaces it with the one in the input.
+fn resolve_item_range_size() -> anyhow::Result<usize> {
+    let item_range_size = match env::var(ITEM_RANGE_SIZE_ENV) {
+        Ok(value) => usize::from_str(value.trim())
+            .with_context(|| format!("{} must be a positive integer, got '{}'", ITEM_RANGE_SIZE_ENV, value))?,
+        Err(env::VarError::NotPresent) => DEFAULT_ITEM_RANGE_SIZE,
+        Err(err) => return Err(err).context(ITEM_RANGE_SIZE_ENV),
+    };
+
+    if item_range_size == 0 {
+        bail!("{} must be greater than 0", ITEM_RANGE_SIZE_ENV);
+    }
+
+    Ok(item_range_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::{Component, MAIN_SEPARATOR};
+
+    #[test]
+    fn mean_rounds_down_without_overflowing() {
+        assert_eq!(<u128 as CliNumber>::mean(&[u128::MAX; 3]), u128::MAX);
+        assert_eq!(<u128 as CliNumber>::mean(&[u128::MAX, 0]), u128::MAX / 2);
+        assert_eq!(<i128 as CliNumber>::mean(&[i128::MIN; 3]), i128::MIN);
+        assert_eq!(<i128 as CliNumber>::mean(&[i128::MAX; 3]), i128::MAX);
+        assert_eq!(<i128 as CliNumber>::mean(&[i128::MIN, i128::MAX]), -1);
+        assert_eq!(<i128 as CliNumber>::mean(&[i128::MIN, i128::MIN, i128::MAX, i128::MAX, i128::MIN]), i128::MIN / 5 - 1);
+        // Small enough to sum directly.
+        for values in [&[-7, 2][..], &[-1], &[-5, -5, -6], &[7, -3, 1, 0], &[-9, 4, 4]] {
+            assert_eq!(<i128 as CliNumber>::mean(values), values.iter().sum::<i128>().div_euclid(values.len() as i128), "{:?}", values);
+        }
+    }
+
+    // Goes through every step after loading as main does for an input file, over bytes built by the test instead.
+    // The global thread pool is left alone, the chunks of more than one thread run on rayon's default pool. Returns the count of invalid numbers, the report is printed as for a file.
+    fn run_bytes(data: &[u8], args: &Args, item_range_size: usize) -> usize {
+        let config = build_config(args, max(1, args.threads), item_range_size, Vec::new(), None);
+        run_on::<u128>(&Input::from(data.to_vec()), Instant::now(), None, args, &config, None).unwrap()
+    }
+
+    #[test]
+    fn run_on_counts_invalid_numbers_without_a_file() {
+        // 20 and 8 have no pair among the two numbers before them.
+        let data = b"1\n2\n3\n5\n20\n8\n28\n";
+        for threads in [1, 2, 3] {
+            let args = Args { threads, ..Args::default() };
+            assert_eq!(run_bytes(data, &args, 2), 2, "{} threads", threads);
+            assert_eq!(run_bytes(b"1\n2\n3\n5\n8\n", &args, 2), 0, "{} threads", threads);
+        }
+        assert_eq!(run_bytes(b"", &Args::default(), 2), 0);
+        assert_eq!(run_bytes(b"\n \n\n", &Args::default(), 2), 0);
+    }
+
+    #[test]
+    fn run_on_skips_blank_lines_and_handles_crlf() {
+        // Still 20 and 8, as in the buffer of run_on_counts_invalid_numbers_without_a_file.
+        let expected = 2;
+        assert_eq!(run_bytes(b"1\n\n2\n3\n\n\n5\n20\n8\n\n28\n", &Args { threads: 3, ..Args::default() }, 2), expected);
+        let crlf = b"1\r\n2\r\n3\r\n5\r\n20\r\n8\r\n28\r\n";
+        assert_eq!(run_bytes(crlf, &Args { threads: 3, crlf: true, ..Args::default() }, 2), expected);
+        assert_eq!(run_bytes(crlf, &Args { threads: 3, ..Args::default() }, 2), expected);
+        assert_eq!(run_bytes(b"1\r\n\r\n2\r\n3\r\n5\r\n\r\n20\r\n8\r\n28", &Args { crlf: true, ..Args::default() }, 2), expected);
+    }
+
+    // Tests run from the package root, which holds the default input.
+    #[test]
+    fn default_path_uses_the_platform_separator() {
+        let file_path = resolve_file_path(None).unwrap();
+        assert_eq!(file_path, env::current_dir().unwrap().join("resources").join("challenge_input.txt"));
+        let tail: Vec<Component> = file_path.components().rev().take(2).collect();
+        assert_eq!(tail, [Component::Normal("challenge_input.txt".as_ref()), Component::Normal("resources".as_ref())]);
+        assert!(file_path.to_string_lossy().ends_with(&format!("{}resources{}challenge_input.txt", MAIN_SEPARATOR, MAIN_SEPARATOR)));
+    }
 }
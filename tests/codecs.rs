@@ -0,0 +1,79 @@
+// Each codec the binary picks by extension yields the same results as the plain input it was made from.
+
+mod common;
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use common::scratch_dir;
+use flate2::write::GzEncoder;
+use serde_json::Value;
+
+const INPUT: &[u8] = include_bytes!("../resources/challenge_input.txt");
+
+// The JSON report without the elapsed time, which differs between runs.
+fn analyze_file(path: &Path) -> Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_gdlauncher-test"))
+        .arg(path)
+        .args(["--format", "json", "--threads", "4"])
+        .env_remove("WINDOW_SIZE")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let mut report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    report.as_object_mut().unwrap().remove("elapsed_micros");
+    report
+}
+
+fn plain_report(dir: &Path) -> Value {
+    let path = dir.join("input.txt");
+    fs::write(&path, INPUT).unwrap();
+    analyze_file(&path)
+}
+
+fn assert_same_as_plain(name: &str, extension: &str, compress: impl FnOnce(&[u8]) -> Vec<u8>) {
+    let dir = scratch_dir(&format!("codecs-{}", name));
+    let expected = plain_report(&dir);
+    assert_eq!(expected["count"], 97);
+    let path = dir.join(format!("input.txt.{}", extension));
+    fs::write(&path, compress(INPUT)).unwrap();
+    let report = analyze_file(&path);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(report, expected);
+}
+
+#[test]
+fn plain() {
+    let dir = scratch_dir("codecs-plain");
+    let report = plain_report(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(report["count"], 97);
+    assert_eq!(report["first_invalid"], 14);
+}
+
+#[test]
+fn gzip() {
+    assert_same_as_plain("gzip", "gz", |data| {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    });
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd() {
+    assert_same_as_plain("zstd", "zst", |data| zstd::encode_all(data, 0).unwrap());
+}
+
+#[cfg(feature = "bzip2")]
+#[test]
+fn bzip2() {
+    assert_same_as_plain("bzip2", "bz2", |data| {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    });
+}
@@ -0,0 +1,13 @@
+// Helpers shared by the integration tests, each of which runs the binary on files it writes.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+// A directory of its own for each test, as they run in parallel.
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("gdlauncher-test-{}-{}", process::id(), name));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
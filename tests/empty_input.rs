@@ -0,0 +1,34 @@
+// Inputs without a single number go through the file path like any other and report nothing invalid, instead of
+// failing on the mapping of an empty file.
+
+mod common;
+
+use std::fs;
+use std::process::Command;
+
+use common::scratch_dir;
+
+fn assert_nothing_invalid(name: &str, contents: &[u8]) {
+    let dir = scratch_dir(name);
+    let path = dir.join("input.txt");
+    fs::write(&path, contents).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_gdlauncher-test"))
+        .arg(&path)
+        .env_remove("WINDOW_SIZE")
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.lines().any(|line| line == "0 invalid numbers found."), "{}", stdout);
+}
+
+#[test]
+fn empty_file() {
+    assert_nothing_invalid("empty-file", b"");
+}
+
+#[test]
+fn whitespace_only_file() {
+    assert_nothing_invalid("whitespace-only-file", b" \n\n\t\r\n  \n");
+}